@@ -10,9 +10,15 @@ use ahash::AHashSet;
 
 use pyo3::prelude::*;
 use pyo3::PyTypeInfo;
-use pyo3::types::{PyBool, PyFloat, PyInt, PyIterator, PyList, PyString, PyTuple};
+use pyo3::types::IntoPyDict;
+use pyo3::types::{PyBool, PyBytes, PyDict, PyFloat, PyInt, PyIterator, PyList, PyString, PyTuple, PyType};
 
-use rust_bench::{PyListBuilder, PyTupleBuilder, list_as_tuple};
+use rust_bench::{
+    BigInt, BorrowedIter, BufferSlice, CacheStats, Checkpoint, ColumnType, ExtractError, ExtractResult, FastIter, FillError, InstanceBuilder, IntValue, Mode, NumberCache, PyKind, PyBytesBuilder, PyListBuilder, PyStringBuilder, PyTupleBuilder, RecordKind, RecordType, RowSetBuilder, StrPath, TypedItem, TypedListBuilder, StringCache, borrow_str, classify, count_prefix,
+    count_suffix, extract_bool,
+    extract_f64, extract_f64_list, extract_i64, extract_i64_list, extract_int, fast_iter, extract_str_arena, extract_str_list, filter_prefix, filter_suffix, list_as_tuple, par_map_i64_list,
+    new_ascii_string, new_latin1_string, Validation, par_map_str_list, to_value, to_value_max_depth, from_value, FromValueOptions, Value, try_extract_bool, try_extract_f64, try_extract_i64, try_extract_str,
+};
 
 
 fn run_startswith_rust(items: &PyList) -> PyResult<i32> {
@@ -127,7 +133,7 @@ fn rust_set_a_hash_set(bench: &mut Bencher) {
 
 fn run_extract_string(py_any: &PyAny) -> bool {
     let str: String = py_any.extract().unwrap();
-    return str == "foobar"
+    str == "foobar"
 }
 
 #[bench]
@@ -143,7 +149,7 @@ fn extract_string(bench: &mut Bencher) {
 fn run_to_string_lossy(py_any: &PyAny) -> bool {
     let py_str: &PyString = py_any.downcast().unwrap();
     let str = py_str.to_string_lossy();
-    return str.as_ref() == "foobar"
+    str.as_ref() == "foobar"
 }
 
 #[bench]
@@ -159,7 +165,7 @@ fn to_string_lossy(bench: &mut Bencher) {
 fn run_to_str(py_any: &PyAny) -> bool {
     let py_str: &PyString = py_any.downcast().unwrap();
     let str = py_str.to_str().unwrap();
-    return str == "foobar"
+    str == "foobar"
 }
 
 #[bench]
@@ -194,11 +200,7 @@ fn is_str_cast_as(bench: &mut Bencher) {
 }
 
 fn run_is_str_extract(py_any: &PyAny) -> Option<String> {
-    if let Ok(str) = py_any.extract::<String>() {
-        Some(str)
-    } else {
-        None
-    }
+    py_any.extract::<String>().ok()
 }
 
 #[bench]
@@ -381,6 +383,8 @@ impl HashVec {
         self.vec.contains(&self.hash(item))
     }
 
+    // deliberately goes through `build_hasher` rather than `hash_one`, that's what this measures
+    #[allow(clippy::manual_hash_one)]
     fn hash(&self, item: &str) -> u64 {
         // let hash = self.hash_builder.hash_one(item);
         let mut hasher = self.hash_builder.build_hasher();
@@ -1040,3 +1044,2624 @@ fn extract_bool_isinstance_success(bench: &mut Bencher) {
         });
     });
 }
+
+///////////////////////// classify
+
+fn run_classify_downcast(py_any: &PyAny) -> PyKind {
+    if py_any.downcast::<PyString>().is_ok() {
+        PyKind::Str
+    } else if py_any.downcast::<PyBool>().is_ok() {
+        PyKind::Bool
+    } else if py_any.downcast::<PyInt>().is_ok() {
+        PyKind::Int
+    } else if py_any.downcast::<PyFloat>().is_ok() {
+        PyKind::Float
+    } else if py_any.is_none() {
+        PyKind::None
+    } else if py_any.downcast::<PyList>().is_ok() {
+        PyKind::List
+    } else if py_any.downcast::<PyDict>().is_ok() {
+        PyKind::Dict
+    } else {
+        PyKind::Other
+    }
+}
+
+fn classify_inputs<'py>(py: Python<'py>) -> Vec<(&'py PyAny, PyKind)> {
+    vec![
+        (PyString::new(py, "foobar"), PyKind::Str),
+        (123.to_object(py).into_ref(py), PyKind::Int),
+        (123.0.to_object(py).into_ref(py), PyKind::Float),
+        (true.to_object(py).into_ref(py), PyKind::Bool),
+        (py.None().into_ref(py), PyKind::None),
+        (PyList::empty(py), PyKind::List),
+        (PyDict::new(py), PyKind::Dict),
+        (PyTuple::empty(py), PyKind::Other),
+        (py.eval("type('MyStr', (str,), {})('foo')", None, None).unwrap(), PyKind::Str),
+        (py.eval("type('MyInt', (int,), {})(42)", None, None).unwrap(), PyKind::Int),
+        (py.eval("type('MyDict', (dict,), {})()", None, None).unwrap(), PyKind::Dict),
+    ]
+}
+
+#[bench]
+fn classify_exact(bench: &mut Bencher) {
+    Python::with_gil(|py| {
+        let inputs = classify_inputs(py);
+        for (input, kind) in &inputs {
+            assert_eq!(classify(input), *kind);
+        }
+
+        bench.iter(|| {
+            for (input, _) in &inputs {
+                black_box(classify(black_box(input)));
+            }
+        });
+    });
+}
+
+#[bench]
+fn classify_downcast(bench: &mut Bencher) {
+    Python::with_gil(|py| {
+        let inputs = classify_inputs(py);
+        for (input, kind) in &inputs {
+            assert_eq!(run_classify_downcast(input), *kind);
+        }
+
+        bench.iter(|| {
+            for (input, _) in &inputs {
+                black_box(run_classify_downcast(black_box(input)));
+            }
+        });
+    });
+}
+
+///////////////////////// borrow_str
+
+fn run_borrow_str(py_any: &PyAny) -> bool {
+    let py_str: &PyString = py_any.downcast().unwrap();
+    let (str, _) = borrow_str(py_str).unwrap();
+    str == "foobar"
+}
+
+#[bench]
+fn borrow_str_ascii(bench: &mut Bencher) {
+    Python::with_gil(|py| {
+        let py_str = PyString::new(py, "foobar");
+        assert_eq!(borrow_str(py_str).unwrap(), ("foobar", StrPath::CompactAscii));
+        let py_any: &PyAny = py_str;
+        bench.iter(|| {
+            black_box(run_borrow_str(black_box(py_any)));
+        });
+    });
+}
+
+const NON_ASCII: &str = "foobar \u{1f980} ça va";
+
+#[bench]
+fn borrow_str_non_ascii(bench: &mut Bencher) {
+    Python::with_gil(|py| {
+        let py_str = PyString::new(py, NON_ASCII);
+        assert_eq!(borrow_str(py_str).unwrap(), (NON_ASCII, StrPath::Utf8));
+        let py_any: &PyAny = py_str;
+        bench.iter(|| {
+            black_box(run_borrow_str(black_box(py_any)));
+        });
+    });
+}
+
+#[bench]
+fn borrow_str_non_ascii_fresh(bench: &mut Bencher) {
+    // a new string each time, so the UTF-8 buffer cached by `PyUnicode_AsUTF8AndSize` can't be reused
+    Python::with_gil(|py| {
+        bench.iter(|| {
+            let py_str = PyString::new(py, black_box(NON_ASCII)).to_object(py);
+            black_box(run_borrow_str(black_box(py_str.as_ref(py))));
+        });
+    });
+}
+
+#[bench]
+fn extract_string_non_ascii(bench: &mut Bencher) {
+    Python::with_gil(|py| {
+        let py_any: &PyAny = PyString::new(py, NON_ASCII);
+        bench.iter(|| {
+            black_box(run_extract_string(black_box(py_any)));
+        });
+    });
+}
+
+#[bench]
+fn to_string_lossy_non_ascii(bench: &mut Bencher) {
+    Python::with_gil(|py| {
+        let py_any: &PyAny = PyString::new(py, NON_ASCII);
+        bench.iter(|| {
+            black_box(run_to_string_lossy(black_box(py_any)));
+        });
+    });
+}
+
+#[bench]
+fn to_str_non_ascii(bench: &mut Bencher) {
+    Python::with_gil(|py| {
+        let py_any: &PyAny = PyString::new(py, NON_ASCII);
+        bench.iter(|| {
+            black_box(run_to_str(black_box(py_any)));
+        });
+    });
+}
+
+///////////////////////// try_extract
+
+#[bench]
+fn try_extract_str_success(bench: &mut Bencher) {
+    Python::with_gil(|py| {
+        let s = PyString::new(py, "Hello, World!") as &PyAny;
+        assert_eq!(try_extract_str(s), Ok("Hello, World!"));
+        let surrogate = py.eval("'\\ud800'", None, None).unwrap();
+        assert_eq!(try_extract_str(surrogate), Err(ExtractError::InvalidUtf8));
+        assert!(PyErr::take(py).is_none());
+
+        bench.iter(|| {
+            let v = try_extract_str(black_box(s)).unwrap();
+            black_box(v);
+        });
+    });
+}
+
+#[bench]
+fn try_extract_str_fail(bench: &mut Bencher) {
+    Python::with_gil(|py| {
+        let d = py.None().into_ref(py);
+
+        bench.iter(|| {
+            match try_extract_str(black_box(d)) {
+                Ok(v) => panic!("should err {}", v),
+                Err(e) => black_box(e),
+            }
+        });
+    });
+}
+
+#[bench]
+fn try_extract_int_success(bench: &mut Bencher) {
+    Python::with_gil(|py| {
+        let int_obj: PyObject = 123.into_py(py);
+        let int = int_obj.as_ref(py);
+        assert_eq!(try_extract_i64(int), Ok(123));
+        let big = py.eval("2 ** 64", None, None).unwrap();
+        assert_eq!(try_extract_i64(big), Err(ExtractError::Overflow));
+
+        bench.iter(|| {
+            let v = try_extract_i64(black_box(int)).unwrap();
+            black_box(v);
+        });
+    });
+}
+
+#[bench]
+fn try_extract_int_fail(bench: &mut Bencher) {
+    Python::with_gil(|py| {
+        let d = py.None().into_ref(py);
+
+        bench.iter(|| {
+            match try_extract_i64(black_box(d)) {
+                Ok(v) => panic!("should err {}", v),
+                Err(e) => black_box(e),
+            }
+        });
+    });
+}
+
+#[bench]
+fn try_extract_float_success(bench: &mut Bencher) {
+    Python::with_gil(|py| {
+        let float_obj: PyObject = 123.0.into_py(py);
+        let float = float_obj.as_ref(py);
+        assert_eq!(try_extract_f64(float), Ok(123.0));
+
+        bench.iter(|| {
+            let v = try_extract_f64(black_box(float)).unwrap();
+            black_box(v);
+        });
+    });
+}
+
+#[bench]
+fn try_extract_float_fail(bench: &mut Bencher) {
+    Python::with_gil(|py| {
+        let d = py.None().into_ref(py);
+
+        bench.iter(|| {
+            match try_extract_f64(black_box(d)) {
+                Ok(v) => panic!("should err {}", v),
+                Err(e) => black_box(e),
+            }
+        });
+    });
+}
+
+#[bench]
+fn try_extract_bool_success(bench: &mut Bencher) {
+    Python::with_gil(|py| {
+        let bool_obj: PyObject = true.into_py(py);
+        let b = bool_obj.as_ref(py);
+        assert_eq!(try_extract_bool(b), Ok(true));
+
+        bench.iter(|| {
+            let v = try_extract_bool(black_box(b)).unwrap();
+            black_box(v);
+        });
+    });
+}
+
+#[bench]
+fn try_extract_bool_fail(bench: &mut Bencher) {
+    Python::with_gil(|py| {
+        let d = py.None().into_ref(py);
+
+        bench.iter(|| {
+            match try_extract_bool(black_box(d)) {
+                Ok(v) => panic!("should err {}", v),
+                Err(e) => black_box(e),
+            }
+        });
+    });
+}
+
+///////////////////////// big int
+
+const DIGITS_200: &str = "-\
+    12345678901234567890123456789012345678901234567890123456789012345678901234567890\
+    12345678901234567890123456789012345678901234567890123456789012345678901234567890\
+    1234567890123456789012345678901234567890";
+
+fn py_int_to_bytes(int: &PyInt) -> Vec<u8> {
+    let n_bytes: usize = int.call_method0("bit_length").unwrap().extract::<usize>().unwrap() / 8 + 1;
+    let kwargs = [("signed", true)].into_py_dict(int.py());
+    int.call_method("to_bytes", (n_bytes, "little"), Some(kwargs)).unwrap().extract().unwrap()
+}
+
+#[bench]
+fn extract_int_big_small(bench: &mut Bencher) {
+    Python::with_gil(|py| {
+        let int_obj: PyObject = 123.into_py(py);
+        let int: &PyInt = int_obj.downcast(py).unwrap();
+        assert_eq!(extract_int(int).unwrap(), IntValue::Int(123));
+
+        bench.iter(|| {
+            black_box(extract_int(black_box(int)).unwrap());
+        });
+    });
+}
+
+#[bench]
+fn extract_int_big_i64_edge(bench: &mut Bencher) {
+    Python::with_gil(|py| {
+        let max: &PyInt = i64::MAX.into_py(py).into_ref(py).downcast().unwrap();
+        let min: &PyInt = i64::MIN.into_py(py).into_ref(py).downcast().unwrap();
+        let over: &PyInt = py.eval("2 ** 63", None, None).unwrap().downcast().unwrap();
+        let under: &PyInt = py.eval("-(2 ** 63) - 1", None, None).unwrap().downcast().unwrap();
+        assert_eq!(extract_int(max).unwrap(), IntValue::Int(i64::MAX));
+        assert_eq!(extract_int(min).unwrap(), IntValue::Int(i64::MIN));
+        match extract_int(over).unwrap() {
+            IntValue::Big(big) => assert_eq!(big.to_i128(), Some(i64::MAX as i128 + 1)),
+            v => panic!("expected big int, got {:?}", v),
+        }
+        match extract_int(under).unwrap() {
+            IntValue::Big(big) => assert_eq!(big.to_i128(), Some(i64::MIN as i128 - 1)),
+            v => panic!("expected big int, got {:?}", v),
+        }
+
+        bench.iter(|| {
+            black_box(extract_int(black_box(max)).unwrap());
+            black_box(extract_int(black_box(min)).unwrap());
+            black_box(extract_int(black_box(over)).unwrap());
+            black_box(extract_int(black_box(under)).unwrap());
+        });
+    });
+}
+
+#[bench]
+fn extract_int_big_200_digits(bench: &mut Bencher) {
+    Python::with_gil(|py| {
+        let int: &PyInt = py.eval(&format!("int('{}')", DIGITS_200), None, None).unwrap().downcast().unwrap();
+        let big: BigInt = match extract_int(int).unwrap() {
+            IntValue::Big(big) => big,
+            v => panic!("expected big int, got {:?}", v),
+        };
+        assert!(big.is_negative());
+        assert_eq!(big.to_i128(), None);
+        assert_eq!(big.to_le_bytes(), py_int_to_bytes(int));
+
+        bench.iter(|| {
+            black_box(extract_int(black_box(int)).unwrap());
+        });
+    });
+}
+
+#[bench]
+fn extract_int_big_200_digits_to_bytes(bench: &mut Bencher) {
+    Python::with_gil(|py| {
+        let int: &PyInt = py.eval(&format!("int('{}')", DIGITS_200), None, None).unwrap().downcast().unwrap();
+
+        bench.iter(|| {
+            black_box(py_int_to_bytes(black_box(int)));
+        });
+    });
+}
+
+///////////////////////// strict vs lax
+
+// (python expression, strict result, lax result)
+type ModeCase<T> = (&'static str, ExtractResult<T>, ExtractResult<T>);
+
+const INT_MODE_CASES: [ModeCase<i64>; 11] = [
+    ("123", Ok(123), Ok(123)),
+    ("True", Err(ExtractError::WrongType), Ok(1)),
+    ("2 ** 64", Err(ExtractError::Overflow), Err(ExtractError::Overflow)),
+    ("type('MyInt', (int,), {})(42)", Err(ExtractError::WrongType), Ok(42)),
+    ("123.0", Err(ExtractError::WrongType), Ok(123)),
+    ("123.5", Err(ExtractError::WrongType), Err(ExtractError::InvalidValue)),
+    ("1e30", Err(ExtractError::WrongType), Err(ExtractError::Overflow)),
+    ("' 123 '", Err(ExtractError::WrongType), Ok(123)),
+    ("'foo'", Err(ExtractError::WrongType), Err(ExtractError::InvalidValue)),
+    ("type('Index', (), {'__index__': lambda self: 7})()", Err(ExtractError::WrongType), Ok(7)),
+    ("None", Err(ExtractError::WrongType), Err(ExtractError::WrongType)),
+];
+
+const FLOAT_MODE_CASES: [ModeCase<f64>; 8] = [
+    ("1.5", Ok(1.5), Ok(1.5)),
+    ("123", Err(ExtractError::WrongType), Ok(123.0)),
+    ("True", Err(ExtractError::WrongType), Ok(1.0)),
+    ("10 ** 400", Err(ExtractError::WrongType), Err(ExtractError::Overflow)),
+    ("'1.5'", Err(ExtractError::WrongType), Ok(1.5)),
+    ("type('Index', (), {'__index__': lambda self: 7})()", Err(ExtractError::WrongType), Ok(7.0)),
+    ("'foo'", Err(ExtractError::WrongType), Err(ExtractError::InvalidValue)),
+    ("None", Err(ExtractError::WrongType), Err(ExtractError::WrongType)),
+];
+
+const BOOL_MODE_CASES: [ModeCase<bool>; 9] = [
+    ("True", Ok(true), Ok(true)),
+    ("False", Ok(false), Ok(false)),
+    ("1", Err(ExtractError::WrongType), Ok(true)),
+    ("2", Err(ExtractError::WrongType), Err(ExtractError::InvalidValue)),
+    ("0.0", Err(ExtractError::WrongType), Ok(false)),
+    ("'yes'", Err(ExtractError::WrongType), Ok(true)),
+    ("'OFF'", Err(ExtractError::WrongType), Ok(false)),
+    ("'foo'", Err(ExtractError::WrongType), Err(ExtractError::InvalidValue)),
+    ("None", Err(ExtractError::WrongType), Err(ExtractError::WrongType)),
+];
+
+fn assert_mode_cases<T: PartialEq + std::fmt::Debug>(
+    py: Python,
+    cases: &[ModeCase<T>],
+    extract: fn(&PyAny, Mode) -> ExtractResult<T>,
+) {
+    for (expr, strict, lax) in cases {
+        let obj = py.eval(expr, None, None).unwrap();
+        assert_eq!(&extract(obj, Mode::Strict), strict, "strict {}", expr);
+        assert_eq!(&extract(obj, Mode::Lax), lax, "lax {}", expr);
+        assert!(PyErr::take(py).is_none(), "error left set by {}", expr);
+    }
+}
+
+fn bench_mode<T>(bench: &mut Bencher, expr: &str, mode: Mode, extract: fn(&PyAny, Mode) -> ExtractResult<T>) {
+    Python::with_gil(|py| {
+        let obj = py.eval(expr, None, None).unwrap();
+        bench.iter(|| {
+            black_box(extract(black_box(obj), mode).ok());
+        });
+    });
+}
+
+#[bench]
+fn extract_int_strict_success(bench: &mut Bencher) {
+    Python::with_gil(|py| assert_mode_cases(py, &INT_MODE_CASES, extract_i64));
+    bench_mode(bench, "123", Mode::Strict, extract_i64);
+}
+
+#[bench]
+fn extract_int_strict_bool_fail(bench: &mut Bencher) {
+    bench_mode(bench, "True", Mode::Strict, extract_i64);
+}
+
+#[bench]
+fn extract_int_lax_int(bench: &mut Bencher) {
+    bench_mode(bench, "123", Mode::Lax, extract_i64);
+}
+
+#[bench]
+fn extract_int_lax_float(bench: &mut Bencher) {
+    bench_mode(bench, "123.0", Mode::Lax, extract_i64);
+}
+
+#[bench]
+fn extract_int_lax_str(bench: &mut Bencher) {
+    bench_mode(bench, "'123'", Mode::Lax, extract_i64);
+}
+
+#[bench]
+fn extract_float_strict_success(bench: &mut Bencher) {
+    Python::with_gil(|py| assert_mode_cases(py, &FLOAT_MODE_CASES, extract_f64));
+    bench_mode(bench, "1.5", Mode::Strict, extract_f64);
+}
+
+#[bench]
+fn extract_float_lax_int(bench: &mut Bencher) {
+    bench_mode(bench, "123", Mode::Lax, extract_f64);
+}
+
+#[bench]
+fn extract_float_lax_str(bench: &mut Bencher) {
+    bench_mode(bench, "'1.5'", Mode::Lax, extract_f64);
+}
+
+#[bench]
+fn extract_bool_strict_success(bench: &mut Bencher) {
+    Python::with_gil(|py| assert_mode_cases(py, &BOOL_MODE_CASES, extract_bool));
+    bench_mode(bench, "True", Mode::Strict, extract_bool);
+}
+
+#[bench]
+fn extract_bool_lax_int(bench: &mut Bencher) {
+    bench_mode(bench, "1", Mode::Lax, extract_bool);
+}
+
+#[bench]
+fn extract_bool_lax_str(bench: &mut Bencher) {
+    bench_mode(bench, "'yes'", Mode::Lax, extract_bool);
+}
+
+///////////////////////// prefix
+
+fn startswith_list<'py>(py: Python<'py>) -> &'py PyList {
+    let items: Vec<PyObject> = (0..100)
+        .map(|i| {
+            if i % 2 == 0 {
+                i.to_string().to_object(py)
+            } else {
+                format!("_{}", i).to_object(py)
+            }
+        })
+        .collect();
+    PyList::new(py, &items)
+}
+
+#[bench]
+fn startswith_count_prefix(bench: &mut Bencher) {
+    Python::with_gil(|py| {
+        let py_list = startswith_list(py);
+        assert_eq!(count_prefix(py_list, "_").unwrap(), 50);
+
+        bench.iter(|| {
+            black_box(count_prefix(black_box(py_list), "_").unwrap());
+        });
+    });
+}
+
+#[bench]
+fn startswith_count_prefix_multi_char(bench: &mut Bencher) {
+    Python::with_gil(|py| {
+        let py_list = startswith_list(py);
+        // _1, _11, _13, ..., _19
+        assert_eq!(count_prefix(py_list, "_1").unwrap(), 6);
+
+        bench.iter(|| {
+            black_box(count_prefix(black_box(py_list), "_1").unwrap());
+        });
+    });
+}
+
+#[bench]
+fn startswith_count_suffix(bench: &mut Bencher) {
+    Python::with_gil(|py| {
+        let py_list = startswith_list(py);
+        assert_eq!(count_suffix(py_list, "9").unwrap(), 10);
+
+        bench.iter(|| {
+            black_box(count_suffix(black_box(py_list), "9").unwrap());
+        });
+    });
+}
+
+#[bench]
+fn startswith_filter_prefix(bench: &mut Bencher) {
+    Python::with_gil(|py| {
+        let py_list = startswith_list(py);
+        let filtered = filter_prefix(py_list, "_").unwrap();
+        assert_eq!(filtered.len(), 50);
+        assert_eq!(filtered.get_item(0).unwrap().extract::<&str>().unwrap(), "_1");
+
+        bench.iter(|| {
+            black_box(filter_prefix(black_box(py_list), "_").unwrap());
+        });
+    });
+}
+
+#[bench]
+fn startswith_string_kinds(bench: &mut Bencher) {
+    Python::with_gil(|py| {
+        // one string per kind: ASCII, latin-1, UCS-2 and UCS-4
+        let py_list = PyList::new(py, ["caf", "café", "€uro", "🦀 crab", "🦀 café"]);
+        assert_eq!(count_prefix(py_list, "caf").unwrap(), 2);
+        assert_eq!(count_prefix(py_list, "€").unwrap(), 1);
+        assert_eq!(count_prefix(py_list, "🦀").unwrap(), 2);
+        assert_eq!(count_suffix(py_list, "é").unwrap(), 2);
+        assert_eq!(count_suffix(py_list, "").unwrap(), 5);
+        let filtered = filter_suffix(py_list, "café").unwrap();
+        assert_eq!(filtered.extract::<Vec<&str>>().unwrap(), vec!["café", "🦀 café"]);
+        assert!(count_prefix(PyList::new(py, [1, 2]), "_").is_err());
+
+        bench.iter(|| {
+            black_box(count_prefix(black_box(py_list), "🦀").unwrap());
+        });
+    });
+}
+
+///////////////////////// str list
+
+fn str_list<'py>(py: Python<'py>, len: usize) -> &'py PyList {
+    let items: Vec<String> = (0..len).map(|i| format!("item {}", i)).collect();
+    PyList::new(py, items)
+}
+
+fn run_str_list_map_extract(list: &PyList) -> PyResult<Vec<&str>> {
+    list.iter().map(|item| item.extract::<&str>()).collect()
+}
+
+#[bench]
+fn str_list_extract_100(bench: &mut Bencher) {
+    Python::with_gil(|py| {
+        let list = str_list(py, 100);
+        let strs = extract_str_list(list).unwrap();
+        assert_eq!(strs, run_str_list_map_extract(list).unwrap());
+        assert_eq!(strs[99], "item 99");
+
+        let mixed = PyList::new(py, ["a".to_object(py), "b".to_object(py), 3.to_object(py)]);
+        let err = extract_str_list(mixed).unwrap_err();
+        assert_eq!(err.to_string(), "TypeError: item 2: expected str, got int");
+
+        bench.iter(|| {
+            black_box(extract_str_list(black_box(list)).unwrap());
+        });
+    });
+}
+
+#[bench]
+fn str_list_extract_10k(bench: &mut Bencher) {
+    Python::with_gil(|py| {
+        let list = str_list(py, 10_000);
+        bench.iter(|| {
+            black_box(extract_str_list(black_box(list)).unwrap());
+        });
+    });
+}
+
+#[bench]
+fn str_list_arena_100(bench: &mut Bencher) {
+    Python::with_gil(|py| {
+        let list = str_list(py, 100);
+        let arena = extract_str_arena(list).unwrap();
+        assert_eq!(arena.len(), 100);
+        assert_eq!(arena.iter().collect::<Vec<_>>(), run_str_list_map_extract(list).unwrap());
+        assert_eq!(arena.get(100), None);
+
+        bench.iter(|| {
+            black_box(extract_str_arena(black_box(list)).unwrap());
+        });
+    });
+}
+
+#[bench]
+fn str_list_arena_10k(bench: &mut Bencher) {
+    Python::with_gil(|py| {
+        let list = str_list(py, 10_000);
+        bench.iter(|| {
+            black_box(extract_str_arena(black_box(list)).unwrap());
+        });
+    });
+}
+
+#[bench]
+fn str_list_map_extract_100(bench: &mut Bencher) {
+    Python::with_gil(|py| {
+        let list = str_list(py, 100);
+        bench.iter(|| {
+            black_box(run_str_list_map_extract(black_box(list)).unwrap());
+        });
+    });
+}
+
+#[bench]
+fn str_list_map_extract_10k(bench: &mut Bencher) {
+    Python::with_gil(|py| {
+        let list = str_list(py, 10_000);
+        bench.iter(|| {
+            black_box(run_str_list_map_extract(black_box(list)).unwrap());
+        });
+    });
+}
+
+///////////////////////// number list
+
+#[bench]
+fn i64_list_extract_fast(bench: &mut Bencher) {
+    Python::with_gil(|py| {
+        let vec_500: Vec<i64> = (0..500).collect();
+        let list = PyList::new(py, &vec_500);
+        assert_eq!(extract_i64_list(list).unwrap(), vec_500);
+        let tuple = PyTuple::new(py, &vec_500);
+        assert_eq!(extract_i64_list(tuple).unwrap(), vec_500);
+
+        // bool and `__index__` go through the fallback
+        let mixed = py.eval("[1, True, type('Index', (), {'__index__': lambda self: 7})()]", None, None).unwrap();
+        assert_eq!(extract_i64_list(mixed).unwrap(), vec![1, 1, 7]);
+        let bad = py.eval("[1, 2, 'x']", None, None).unwrap();
+        let err = extract_i64_list(bad).unwrap_err();
+        assert_eq!(err.to_string(), "TypeError: item 2: 'str' object cannot be interpreted as an integer");
+        let big = py.eval("(1, 2 ** 64)", None, None).unwrap();
+        let err = extract_i64_list(big).unwrap_err();
+        // overflow keeps its type so callers can still catch it
+        assert!(err.to_string().starts_with("OverflowError: item 1: "), "{}", err);
+        assert!(!err.to_string().starts_with("OverflowError: item 1: OverflowError"), "{}", err);
+        assert!(err.cause(py).unwrap().is_instance_of::<pyo3::exceptions::PyOverflowError>(py));
+        // the original exception is kept as the cause, however its constructor is defined
+        let code = r#"
+class Bad(Exception):
+    def __init__(self, code, detail):
+        super().__init__(code, detail)
+
+class BadIndex:
+    def __index__(self):
+        raise Bad(42, 'nope')
+
+class InterruptIndex:
+    def __index__(self):
+        raise KeyboardInterrupt
+
+bad_index = [1, BadIndex()]
+interrupt_index = [1, 2, InterruptIndex()]
+"#;
+        let module = PyModule::from_code(py, code, "bad_index.py", "bad_index").unwrap();
+        let err = extract_i64_list(module.getattr("bad_index").unwrap()).unwrap_err();
+        assert_eq!(err.to_string(), "ValueError: item 1: (42, 'nope')");
+        let cause = err.cause(py).unwrap();
+        assert!(cause.get_type(py).is(module.getattr("Bad").unwrap()));
+        assert_eq!(cause.value(py).getattr("args").unwrap().to_string(), "(42, 'nope')");
+        // read from the value since `PyErr::cause` doesn't fill in `traceback()`
+        assert!(!cause.value(py).getattr("__traceback__").unwrap().is_none());
+        // exceptions that aren't about the item aren't wrapped
+        let err = extract_i64_list(module.getattr("interrupt_index").unwrap()).unwrap_err();
+        assert!(err.get_type(py).is(py.get_type::<pyo3::exceptions::PyKeyboardInterrupt>()));
+        assert!(err.cause(py).is_none());
+        assert!(extract_i64_list(py.eval("{1, 2}", None, None).unwrap()).is_err());
+
+        bench.iter(|| {
+            black_box(extract_i64_list(black_box(list)).unwrap());
+        });
+    });
+}
+
+#[bench]
+fn i64_list_extract_pyo3(bench: &mut Bencher) {
+    Python::with_gil(|py| {
+        let vec_500: Vec<i64> = (0..500).collect();
+        let list = PyList::new(py, &vec_500);
+
+        bench.iter(|| {
+            black_box(black_box(list).extract::<Vec<i64>>().unwrap());
+        });
+    });
+}
+
+#[bench]
+fn i64_tuple_extract_fast(bench: &mut Bencher) {
+    Python::with_gil(|py| {
+        let vec_500: Vec<i64> = (0..500).collect();
+        let tuple = PyTuple::new(py, &vec_500);
+
+        bench.iter(|| {
+            black_box(extract_i64_list(black_box(tuple)).unwrap());
+        });
+    });
+}
+
+#[bench]
+fn f64_list_extract_fast(bench: &mut Bencher) {
+    Python::with_gil(|py| {
+        let vec_500: Vec<f64> = (0..500).map(|i| i as f64 / 2.0).collect();
+        let list = PyList::new(py, &vec_500);
+        assert_eq!(extract_f64_list(list).unwrap(), vec_500);
+        // ints go through the fallback
+        assert_eq!(extract_f64_list(PyList::new(py, [1, 2])).unwrap(), vec![1.0, 2.0]);
+        let err = extract_f64_list(PyList::new(py, [Some(1.0), None])).unwrap_err();
+        assert_eq!(err.to_string(), "TypeError: item 1: must be real number, not NoneType");
+
+        bench.iter(|| {
+            black_box(extract_f64_list(black_box(list)).unwrap());
+        });
+    });
+}
+
+#[bench]
+fn f64_list_extract_pyo3(bench: &mut Bencher) {
+    Python::with_gil(|py| {
+        let vec_500: Vec<f64> = (0..500).map(|i| i as f64 / 2.0).collect();
+        let list = PyList::new(py, &vec_500);
+
+        bench.iter(|| {
+            black_box(black_box(list).extract::<Vec<f64>>().unwrap());
+        });
+    });
+}
+
+///////////////////////// buffer
+
+fn run_buffer_sum(py: Python, obj: &PyAny) -> PyResult<i64> {
+    let buffer = BufferSlice::<i64>::new(obj)?;
+    let sum = unsafe { buffer.as_slice(py) }.iter().sum();
+    buffer.release(py);
+    Ok(sum)
+}
+
+fn run_list_sum(obj: &PyAny) -> PyResult<i64> {
+    Ok(extract_i64_list(obj)?.iter().sum())
+}
+
+#[bench]
+fn buffer_array_sum_500(bench: &mut Bencher) {
+    Python::with_gil(|py| {
+        let array = py.eval("__import__('array').array('q', range(500))", None, None).unwrap();
+        let list = py.eval("list(range(500))", None, None).unwrap();
+        assert_eq!(run_buffer_sum(py, array).unwrap(), run_list_sum(list).unwrap());
+
+        let memoryview = py.eval("memoryview(__import__('array').array('q', [1, 2, 3]))", None, None).unwrap();
+        assert_eq!(run_buffer_sum(py, memoryview).unwrap(), 6);
+        let bytes = py.eval("b'abc'", None, None).unwrap();
+        let bytes_buffer = BufferSlice::<u8>::new(bytes).unwrap();
+        assert!(bytes_buffer.readonly());
+        assert_eq!(unsafe { bytes_buffer.as_slice(py) }, b"abc");
+        // wrong element type
+        assert!(BufferSlice::<i64>::new(bytes).is_err());
+        let strided = py.eval("memoryview(__import__('array').array('q', range(10)))[::2]", None, None).unwrap();
+        assert!(BufferSlice::<i64>::new(strided).is_err());
+
+        bench.iter(|| {
+            black_box(run_buffer_sum(py, black_box(array)).unwrap());
+        });
+    });
+}
+
+#[bench]
+fn buffer_list_sum_500(bench: &mut Bencher) {
+    Python::with_gil(|py| {
+        let list = py.eval("list(range(500))", None, None).unwrap();
+        bench.iter(|| {
+            black_box(run_list_sum(black_box(list)).unwrap());
+        });
+    });
+}
+
+#[bench]
+fn buffer_array_sum_10k(bench: &mut Bencher) {
+    Python::with_gil(|py| {
+        let array = py.eval("__import__('array').array('q', range(10_000))", None, None).unwrap();
+        bench.iter(|| {
+            black_box(run_buffer_sum(py, black_box(array)).unwrap());
+        });
+    });
+}
+
+#[bench]
+fn buffer_list_sum_10k(bench: &mut Bencher) {
+    Python::with_gil(|py| {
+        let list = py.eval("list(range(10_000))", None, None).unwrap();
+        bench.iter(|| {
+            black_box(run_list_sum(black_box(list)).unwrap());
+        });
+    });
+}
+
+///////////////////////// fast_iter
+
+const ITER_CLASSES: &str = "
+class CustomIter:
+    def __iter__(self):
+        return iter(range(500))
+
+class CustomSeq:
+    def __len__(self):
+        return 500
+    def __getitem__(self, index):
+        if index >= 500:
+            raise IndexError(index)
+        return index
+
+# empty as a list, only `__iter__` gives the items
+class ListSubclass(list):
+    def __iter__(self):
+        return iter(range(500))
+";
+
+// each input is built by calling a zero-argument function, so generators can be recreated
+fn iter_input<'py>(py: Python<'py>, expr: &str) -> &'py PyAny {
+    let globals = PyDict::new(py);
+    py.run(ITER_CLASSES, Some(globals), None).unwrap();
+    py.eval(&format!("lambda: {}", expr), Some(globals), None).unwrap()
+}
+
+fn run_fast_iter(obj: &PyAny) -> PyResult<i64> {
+    let mut total = 0;
+    for item in fast_iter(obj)? {
+        total += item?.extract::<i64>()?;
+    }
+    Ok(total)
+}
+
+fn run_any_iter(obj: &PyAny) -> PyResult<i64> {
+    let mut total = 0;
+    for item in obj.iter()? {
+        total += item?.extract::<i64>()?;
+    }
+    Ok(total)
+}
+
+fn bench_iter_input(bench: &mut Bencher, expr: &str, run: fn(&PyAny) -> PyResult<i64>) {
+    Python::with_gil(|py| {
+        let make_input = iter_input(py, expr);
+        let expected: i64 = (0..500).sum();
+        assert_eq!(run(make_input.call0().unwrap()).unwrap(), expected);
+
+        bench.iter(|| {
+            let input = make_input.call0().unwrap();
+            black_box(run(black_box(input)).unwrap());
+        });
+    });
+}
+
+#[bench]
+fn fast_iter_list(bench: &mut Bencher) {
+    Python::with_gil(|py| {
+        let strategy = |expr: &str| fast_iter(iter_input(py, expr).call0().unwrap()).unwrap();
+        assert!(matches!(strategy("[1, 2]"), FastIter::List { .. }));
+        assert!(matches!(strategy("(1, 2)"), FastIter::Tuple { .. }));
+        assert!(matches!(strategy("type('MyTuple', (tuple,), {})()"), FastIter::Iter(_)));
+        assert!(matches!(strategy("ListSubclass()"), FastIter::Iter(_)));
+        assert!(matches!(strategy("range(2)"), FastIter::List { .. }));
+        assert!(matches!(strategy("CustomSeq()"), FastIter::List { .. }));
+        assert!(matches!(strategy("{1, 2}"), FastIter::Iter(_)));
+        assert!(matches!(strategy("CustomIter()"), FastIter::Iter(_)));
+    });
+    bench_iter_input(bench, "list(range(500))", run_fast_iter);
+}
+
+#[bench]
+fn fast_iter_tuple(bench: &mut Bencher) {
+    bench_iter_input(bench, "tuple(range(500))", run_fast_iter);
+}
+
+#[bench]
+fn fast_iter_list_subclass(bench: &mut Bencher) {
+    bench_iter_input(bench, "ListSubclass()", run_fast_iter);
+}
+
+#[bench]
+fn fast_iter_range(bench: &mut Bencher) {
+    bench_iter_input(bench, "range(500)", run_fast_iter);
+}
+
+#[bench]
+fn fast_iter_custom_seq(bench: &mut Bencher) {
+    bench_iter_input(bench, "CustomSeq()", run_fast_iter);
+}
+
+#[bench]
+fn fast_iter_dict_keys(bench: &mut Bencher) {
+    bench_iter_input(bench, "dict.fromkeys(range(500)).keys()", run_fast_iter);
+}
+
+#[bench]
+fn fast_iter_set(bench: &mut Bencher) {
+    bench_iter_input(bench, "set(range(500))", run_fast_iter);
+}
+
+#[bench]
+fn fast_iter_generator(bench: &mut Bencher) {
+    bench_iter_input(bench, "(i for i in range(500))", run_fast_iter);
+}
+
+#[bench]
+fn fast_iter_custom_iter(bench: &mut Bencher) {
+    bench_iter_input(bench, "CustomIter()", run_fast_iter);
+}
+
+#[bench]
+fn any_iter_list(bench: &mut Bencher) {
+    bench_iter_input(bench, "list(range(500))", run_any_iter);
+}
+
+#[bench]
+fn any_iter_tuple(bench: &mut Bencher) {
+    bench_iter_input(bench, "tuple(range(500))", run_any_iter);
+}
+
+#[bench]
+fn any_iter_range(bench: &mut Bencher) {
+    bench_iter_input(bench, "range(500)", run_any_iter);
+}
+
+#[bench]
+fn any_iter_custom_seq(bench: &mut Bencher) {
+    bench_iter_input(bench, "CustomSeq()", run_any_iter);
+}
+
+#[bench]
+fn any_iter_dict_keys(bench: &mut Bencher) {
+    bench_iter_input(bench, "dict.fromkeys(range(500)).keys()", run_any_iter);
+}
+
+#[bench]
+fn any_iter_set(bench: &mut Bencher) {
+    bench_iter_input(bench, "set(range(500))", run_any_iter);
+}
+
+#[bench]
+fn any_iter_generator(bench: &mut Bencher) {
+    bench_iter_input(bench, "(i for i in range(500))", run_any_iter);
+}
+
+#[bench]
+fn any_iter_custom_iter(bench: &mut Bencher) {
+    bench_iter_input(bench, "CustomIter()", run_any_iter);
+}
+
+///////////////////////// borrowed iteration
+
+fn run_borrowed_iter_sum(list: &PyList) -> PyResult<i64> {
+    let mut total = 0;
+    for item in unsafe { BorrowedIter::list(list) } {
+        total += try_extract_i64(item?).unwrap();
+    }
+    Ok(total)
+}
+
+fn run_list_iter_sum(list: &PyList) -> PyResult<i64> {
+    let mut total = 0;
+    for item in list.iter() {
+        total += try_extract_i64(item).unwrap();
+    }
+    Ok(total)
+}
+
+#[bench]
+fn borrowed_list_iter(bench: &mut Bencher) {
+    Python::with_gil(|py| {
+        let vec_500: Vec<i64> = (0..500).collect();
+        let list: &PyList = PyList::new(py, &vec_500);
+        assert_eq!(run_borrowed_iter_sum(list).unwrap(), vec_500.iter().sum::<i64>());
+
+        let tuple = PyTuple::new(py, [1, 2, 3]);
+        let items: Vec<i64> = BorrowedIter::tuple(tuple).map(|item| item.unwrap().extract().unwrap()).collect();
+        assert_eq!(items, vec![1, 2, 3]);
+
+        // growing the list reallocates its storage and changes the length, both are caught
+        let small_list = PyList::new(py, [1, 2, 3]);
+        let mut iter = unsafe { BorrowedIter::list(small_list) };
+        assert!(iter.next().unwrap().is_ok());
+        small_list.append(4).unwrap();
+        let err = iter.next().unwrap().unwrap_err();
+        assert_eq!(err.to_string(), "RuntimeError: list mutated during iteration");
+        // the error ends the iteration, so loops that skip errors still terminate
+        assert!(iter.next().is_none());
+        assert!(iter.next().is_none());
+        let mut iter = unsafe { BorrowedIter::list(small_list) };
+        iter.next();
+        small_list.append(5).unwrap();
+        assert_eq!(iter.filter_map(Result::ok).count(), 0);
+
+        bench.iter(|| {
+            black_box(run_borrowed_iter_sum(black_box(list)).unwrap());
+        });
+    });
+}
+
+#[bench]
+fn borrowed_list_iter_pyo3(bench: &mut Bencher) {
+    Python::with_gil(|py| {
+        let vec_500: Vec<i64> = (0..500).collect();
+        let list: &PyList = PyList::new(py, &vec_500);
+
+        bench.iter(|| {
+            black_box(run_list_iter_sum(black_box(list)).unwrap());
+        });
+    });
+}
+
+///////////////////////// parallel
+
+const PAR_THREADS: usize = 4;
+
+fn par_str_list<'py>(py: Python<'py>, len: usize) -> &'py PyList {
+    let items: Vec<String> = (0..len).map(|i| if i % 2 == 0 { format!("_{}", i) } else { i.to_string() }).collect();
+    PyList::new(py, items)
+}
+
+// deliberately does some real work per item, a bare `starts_with` is too cheap to ever be worth
+// spreading across threads
+fn par_str_check(s: &str) -> bool {
+    s.starts_with('_') && s.bytes().map(|b| b as u32).sum::<u32>() % 3 == 0
+}
+
+fn bench_par_str(bench: &mut Bencher, len: usize, n_threads: usize) {
+    Python::with_gil(|py| {
+        let list = par_str_list(py, len);
+        let expected: Vec<bool> = (0..len)
+            .map(|i| if i % 2 == 0 { format!("_{}", i) } else { i.to_string() })
+            .map(|s| par_str_check(&s))
+            .collect();
+        let result = par_map_str_list(list, n_threads, par_str_check).unwrap();
+        assert_eq!(result.extract::<Vec<bool>>().unwrap(), expected);
+
+        bench.iter(|| {
+            black_box(par_map_str_list(black_box(list), n_threads, par_str_check).unwrap());
+        });
+    });
+}
+
+#[bench]
+fn par_str_100_serial(bench: &mut Bencher) {
+    bench_par_str(bench, 100, 1);
+}
+
+#[bench]
+fn par_str_100_parallel(bench: &mut Bencher) {
+    bench_par_str(bench, 100, PAR_THREADS);
+}
+
+#[bench]
+fn par_str_10k_serial(bench: &mut Bencher) {
+    bench_par_str(bench, 10_000, 1);
+}
+
+#[bench]
+fn par_str_10k_parallel(bench: &mut Bencher) {
+    bench_par_str(bench, 10_000, PAR_THREADS);
+}
+
+#[bench]
+fn par_str_100k_serial(bench: &mut Bencher) {
+    bench_par_str(bench, 100_000, 1);
+}
+
+#[bench]
+fn par_str_100k_parallel(bench: &mut Bencher) {
+    bench_par_str(bench, 100_000, PAR_THREADS);
+}
+
+fn bench_par_i64(bench: &mut Bencher, len: i64, n_threads: usize) {
+    Python::with_gil(|py| {
+        let vec: Vec<i64> = (0..len).collect();
+        let list = PyList::new(py, &vec);
+        let mut set: AHashSet<i64> = AHashSet::with_capacity(100);
+        for i in 0..100 {
+            set.insert(i * 7);
+        }
+        let result = par_map_i64_list(list, n_threads, |i| set.contains(&i)).unwrap();
+        assert_eq!(result.extract::<Vec<bool>>().unwrap(), vec.iter().map(|i| set.contains(i)).collect::<Vec<_>>());
+
+        let bad = py
+            .eval("[1, type('I', (), {'__index__': lambda self: (_ for _ in ()).throw(KeyError('k'))})()]", None, None)
+            .unwrap()
+            .downcast::<PyList>()
+            .unwrap();
+        let err = par_map_i64_list(bad, n_threads, |i| i).unwrap_err();
+        assert_eq!(err.to_string(), "ValueError: item 1: 'k'");
+        assert!(err.cause(py).unwrap().is_instance_of::<pyo3::exceptions::PyKeyError>(py));
+
+        bench.iter(|| {
+            black_box(par_map_i64_list(black_box(list), n_threads, |i| set.contains(&i)).unwrap());
+        });
+    });
+}
+
+#[bench]
+fn par_i64_contains_10k_serial(bench: &mut Bencher) {
+    bench_par_i64(bench, 10_000, 1);
+}
+
+#[bench]
+fn par_i64_contains_10k_parallel(bench: &mut Bencher) {
+    bench_par_i64(bench, 10_000, PAR_THREADS);
+}
+
+#[bench]
+fn par_i64_contains_100k_serial(bench: &mut Bencher) {
+    bench_par_i64(bench, 100_000, 1);
+}
+
+#[bench]
+fn par_i64_contains_100k_parallel(bench: &mut Bencher) {
+    bench_par_i64(bench, 100_000, PAR_THREADS);
+}
+
+///////////////////////// to_value
+
+const PAYLOAD: &str = "[
+    {
+        'id': i,
+        'name': f'user {i}',
+        'email': f'user{i}@example.com',
+        'active': i % 3 == 0,
+        'score': i * 1.5,
+        'balance': 10 ** 30 if i == 0 else -i,
+        'tags': ['admin', 'staff'] if i % 2 else [],
+        'address': {'street': f'{i} High Street', 'city': 'London', 'postcode': None},
+        'history': [{'ts': 1_600_000_000 + j, 'event': 'login', 'ok': True} for j in range(3)],
+    }
+    for i in range(100)
+]";
+
+fn run_to_value_downcast(obj: &PyAny) -> PyResult<Value> {
+    if obj.is_none() {
+        Ok(Value::Null)
+    } else if let Ok(b) = obj.downcast::<PyBool>() {
+        Ok(Value::Bool(b.is_true()))
+    } else if let Ok(i) = obj.downcast::<PyInt>() {
+        Ok(Value::Int(i.extract()?))
+    } else if let Ok(f) = obj.downcast::<PyFloat>() {
+        Ok(Value::Float(f.value()))
+    } else if let Ok(s) = obj.downcast::<PyString>() {
+        Ok(Value::Str(s.to_str()?.to_string()))
+    } else if let Ok(list) = obj.downcast::<PyList>() {
+        list.iter().map(run_to_value_downcast).collect::<PyResult<_>>().map(Value::Array)
+    } else if let Ok(dict) = obj.downcast::<PyDict>() {
+        dict.iter()
+            .map(|(k, v)| Ok((k.extract::<String>()?, run_to_value_downcast(v)?)))
+            .collect::<PyResult<_>>()
+            .map(Value::Object)
+    } else {
+        Err(pyo3::exceptions::PyTypeError::new_err("unsupported type"))
+    }
+}
+
+#[bench]
+fn to_value_payload(bench: &mut Bencher) {
+    Python::with_gil(|py| {
+        let payload = py.eval(PAYLOAD, None, None).unwrap();
+        let value = to_value(payload).unwrap();
+        let records = match value {
+            Value::Array(records) => records,
+            v => panic!("expected array, got {:?}", v),
+        };
+        assert_eq!(records.len(), 100);
+        match &records[1] {
+            Value::Object(fields) => {
+                assert_eq!(fields[0], ("id".to_string(), Value::Int(1)));
+                assert_eq!(fields[1], ("name".to_string(), Value::Str("user 1".to_string())));
+                assert_eq!(fields[3], ("active".to_string(), Value::Bool(false)));
+                assert_eq!(fields[4], ("score".to_string(), Value::Float(1.5)));
+                assert_eq!(fields[5], ("balance".to_string(), Value::Int(-1)));
+            }
+            v => panic!("expected object, got {:?}", v),
+        }
+        match &records[0] {
+            Value::Object(fields) => assert!(matches!(fields[5].1, Value::BigInt(_))),
+            v => panic!("expected object, got {:?}", v),
+        }
+
+        let cyclic = py.eval("(lambda l: (l.append(l), l)[1])([1])", None, None).unwrap();
+        assert_eq!(to_value(cyclic).unwrap_err().to_string(), "ValueError: circular reference detected");
+        // the same object twice isn't a cycle
+        let shared = py.eval("(lambda d: [d, d, (d,)])({'a': 1})", None, None).unwrap();
+        assert!(to_value(shared).is_ok());
+        let deep = py.eval("[[[[1]]]]", None, None).unwrap();
+        assert!(to_value_max_depth(deep, 4).is_ok());
+        assert_eq!(to_value_max_depth(deep, 3).unwrap_err().to_string(), "ValueError: max depth of 3 exceeded");
+        let bad_key = py.eval("{1: 2}", None, None).unwrap();
+        assert_eq!(to_value(bad_key).unwrap_err().to_string(), "TypeError: expected str, got int");
+        let unsupported = py.eval("[1, {2}]", None, None).unwrap();
+        assert_eq!(to_value(unsupported).unwrap_err().to_string(), "TypeError: unsupported type: set");
+
+        bench.iter(|| {
+            black_box(to_value(black_box(payload)).unwrap());
+        });
+    });
+}
+
+#[bench]
+fn to_value_payload_downcast(bench: &mut Bencher) {
+    Python::with_gil(|py| {
+        // no big ints, the naive version can't handle them
+        let payload = py.eval(&PAYLOAD.replace("10 ** 30", "0"), None, None).unwrap();
+        assert_eq!(run_to_value_downcast(payload).unwrap(), to_value(payload).unwrap());
+
+        bench.iter(|| {
+            black_box(run_to_value_downcast(black_box(payload)).unwrap());
+        });
+    });
+}
+
+#[bench]
+fn to_value_deep(bench: &mut Bencher) {
+    Python::with_gil(|py| {
+        let deep = py.eval("__import__('functools').reduce(lambda acc, _: {'child': [acc]}, range(90), 1)", None, None).unwrap();
+        assert!(to_value(deep).is_ok());
+        assert!(to_value_max_depth(deep, 100).is_err());
+
+        bench.iter(|| {
+            black_box(to_value(black_box(deep)).unwrap());
+        });
+    });
+}
+
+///////////////////////// from_value
+
+fn run_from_value_naive(py: Python, value: &Value) -> PyObject {
+    match value {
+        Value::Null => py.None(),
+        Value::Bool(b) => b.to_object(py),
+        Value::Int(i) => i.to_object(py),
+        Value::BigInt(big) => big.to_object(py),
+        Value::Float(f) => f.to_object(py),
+        Value::Str(s) => s.to_object(py),
+        Value::Array(items) => {
+            let items: Vec<PyObject> = items.iter().map(|item| run_from_value_naive(py, item)).collect();
+            items.to_object(py)
+        }
+        Value::Object(fields) => {
+            let dict = PyDict::new(py);
+            for (key, value) in fields {
+                dict.set_item(key, run_from_value_naive(py, value)).unwrap();
+            }
+            dict.to_object(py)
+        }
+    }
+}
+
+fn bench_from_value(bench: &mut Bencher, options: FromValueOptions) {
+    Python::with_gil(|py| {
+        let payload = py.eval(PAYLOAD, None, None).unwrap();
+        let value = to_value(payload).unwrap();
+
+        bench.iter(|| {
+            black_box(from_value(py, black_box(&value), &options).unwrap());
+        });
+    });
+}
+
+#[bench]
+fn from_value_payload(bench: &mut Bencher) {
+    Python::with_gil(|py| {
+        let payload = py.eval(PAYLOAD, None, None).unwrap();
+        let value = to_value(payload).unwrap();
+        let round_trip = from_value(py, &value, &FromValueOptions::default()).unwrap();
+        assert!(round_trip.as_ref(py).eq(payload).unwrap());
+        assert!(run_from_value_naive(py, &value).as_ref(py).eq(payload).unwrap());
+
+        let tuples = FromValueOptions { sequences_as_tuples: true, ..Default::default() };
+        let as_tuples = from_value(py, &value, &tuples).unwrap().into_ref(py);
+        assert!(as_tuples.is_instance_of::<PyTuple>().unwrap());
+        assert!(as_tuples.get_item(0).unwrap().get_item("tags").unwrap().is_instance_of::<PyTuple>().unwrap());
+
+        let interned = FromValueOptions { intern_keys: true, ..Default::default() };
+        let records = from_value(py, &value, &interned).unwrap().into_ref(py);
+        let first_key = |i: usize| records.get_item(i).unwrap().iter().unwrap().next().unwrap().unwrap();
+        assert!(first_key(0).is(first_key(1)));
+    });
+    bench_from_value(bench, FromValueOptions::default());
+}
+
+#[bench]
+fn from_value_payload_tuples(bench: &mut Bencher) {
+    bench_from_value(bench, FromValueOptions { sequences_as_tuples: true, ..Default::default() });
+}
+
+#[bench]
+fn from_value_payload_intern_keys(bench: &mut Bencher) {
+    bench_from_value(bench, FromValueOptions { intern_keys: true, ..Default::default() });
+}
+
+#[bench]
+fn from_value_payload_naive(bench: &mut Bencher) {
+    Python::with_gil(|py| {
+        let payload = py.eval(PAYLOAD, None, None).unwrap();
+        let value = to_value(payload).unwrap();
+
+        bench.iter(|| {
+            black_box(run_from_value_naive(py, black_box(&value)));
+        });
+    });
+}
+
+///////////////////////// string cache
+
+// 10k strings drawn from `distinct` different values
+fn repeated_keys(distinct: usize) -> Vec<String> {
+    (0..10_000).map(|i| format!("_{}", i % distinct)).collect()
+}
+
+fn run_keys_cache<'py>(py: Python<'py>, cache: &mut StringCache, keys: &[String]) -> PyResult<&'py PyList> {
+    let mut list_builder = PyListBuilder::with_capacity(py, keys.len())?;
+    for key in keys {
+        list_builder.push_alt(py, cache.get(py, key))?;
+    }
+    list_builder.get(py)
+}
+
+fn run_keys_no_cache<'py>(py: Python<'py>, keys: &[String]) -> PyResult<&'py PyList> {
+    let mut list_builder = PyListBuilder::with_capacity(py, keys.len())?;
+    for key in keys {
+        list_builder.push_alt(py, key.to_object(py))?;
+    }
+    list_builder.get(py)
+}
+
+fn run_keys_intern<'py>(py: Python<'py>, keys: &[String]) -> PyResult<&'py PyList> {
+    let mut list_builder = PyListBuilder::with_capacity(py, keys.len())?;
+    for key in keys {
+        list_builder.push_alt(py, PyString::intern(py, key))?;
+    }
+    list_builder.get(py)
+}
+
+#[bench]
+fn string_cache_repeated_keys(bench: &mut Bencher) {
+    Python::with_gil(|py| {
+        let keys = repeated_keys(100);
+        let mut cache = StringCache::with_capacity(1024);
+        let list = run_keys_cache(py, &mut cache, &keys).unwrap();
+        assert!(list.eq(run_keys_no_cache(py, &keys).unwrap()).unwrap());
+        let stats = cache.stats();
+        assert_eq!(stats.hits + stats.misses, 10_000);
+        assert!(stats.hit_rate() > 0.9, "{:?}", stats);
+
+        // a single pair of slots
+        let mut tiny = StringCache::with_capacity(1);
+        assert_eq!(tiny.capacity(), 2);
+        let a = tiny.get(py, "a");
+        // the same key gives the same object
+        assert!(a.as_ref(py).is(tiny.get(py, "a").as_ref(py)));
+        tiny.get(py, "b");
+        tiny.get(py, "c");
+        assert_eq!(tiny.stats(), CacheStats { hits: 1, misses: 3, evictions: 1 });
+        tiny.clear();
+        assert_eq!(tiny.stats(), CacheStats::default());
+
+        bench.iter(|| {
+            black_box(run_keys_cache(py, &mut cache, black_box(&keys)).unwrap());
+        });
+    });
+}
+
+#[bench]
+fn string_cache_repeated_keys_no_cache(bench: &mut Bencher) {
+    Python::with_gil(|py| {
+        let keys = repeated_keys(100);
+        bench.iter(|| {
+            black_box(run_keys_no_cache(py, black_box(&keys)).unwrap());
+        });
+    });
+}
+
+#[bench]
+fn string_cache_repeated_keys_intern(bench: &mut Bencher) {
+    Python::with_gil(|py| {
+        let keys = repeated_keys(100);
+        bench.iter(|| {
+            black_box(run_keys_intern(py, black_box(&keys)).unwrap());
+        });
+    });
+}
+
+#[bench]
+fn string_cache_unique_keys(bench: &mut Bencher) {
+    // the worst case: every lookup misses and evicts
+    Python::with_gil(|py| {
+        let keys = repeated_keys(10_000);
+        let mut cache = StringCache::with_capacity(1024);
+        bench.iter(|| {
+            black_box(run_keys_cache(py, &mut cache, black_box(&keys)).unwrap());
+        });
+    });
+}
+
+#[bench]
+fn string_cache_unique_keys_no_cache(bench: &mut Bencher) {
+    Python::with_gil(|py| {
+        let keys = repeated_keys(10_000);
+        bench.iter(|| {
+            black_box(run_keys_no_cache(py, black_box(&keys)).unwrap());
+        });
+    });
+}
+
+///////////////////////// number cache
+
+// 10k values drawn from 50 distinct large ints, as you'd get from e.g. status codes or ids
+fn repeated_ints() -> Vec<i64> {
+    (0..10_000).map(|i| (i % 50) * 1_000).collect()
+}
+
+fn repeated_floats() -> Vec<f64> {
+    (0..10_000).map(|i| (i % 50) as f64 * 0.5).collect()
+}
+
+fn run_cached_ints<'py>(py: Python<'py>, cache: &mut NumberCache, input: &[i64]) -> PyResult<&'py PyList> {
+    let mut list_builder = PyListBuilder::with_capacity(py, input.len())?;
+    for i in input {
+        list_builder.push(py, cache.int(py, *i))?;
+    }
+    list_builder.get(py)
+}
+
+fn run_uncached_ints<'py>(py: Python<'py>, input: &[i64]) -> PyResult<&'py PyList> {
+    let mut list_builder = PyListBuilder::with_capacity(py, input.len())?;
+    for i in input {
+        list_builder.push(py, i)?;
+    }
+    list_builder.get(py)
+}
+
+fn run_cached_floats<'py>(py: Python<'py>, cache: &mut NumberCache, input: &[f64]) -> PyResult<&'py PyList> {
+    let mut list_builder = PyListBuilder::with_capacity(py, input.len())?;
+    for f in input {
+        list_builder.push(py, cache.float(py, *f))?;
+    }
+    list_builder.get(py)
+}
+
+fn run_uncached_floats<'py>(py: Python<'py>, input: &[f64]) -> PyResult<&'py PyList> {
+    let mut list_builder = PyListBuilder::with_capacity(py, input.len())?;
+    for f in input {
+        list_builder.push(py, f)?;
+    }
+    list_builder.get(py)
+}
+
+#[bench]
+fn number_cache_repeated_ints(bench: &mut Bencher) {
+    Python::with_gil(|py| {
+        let input = repeated_ints();
+        let mut cache = NumberCache::with_capacity(py, 256);
+        let list = run_cached_ints(py, &mut cache, &input).unwrap();
+        assert!(list.eq(run_uncached_ints(py, &input).unwrap()).unwrap());
+        let stats = cache.stats();
+        // the 200 zeros are small ints, which bypass the cache and aren't counted
+        assert_eq!(stats.hits + stats.misses, 9_800);
+        assert!(stats.hit_rate() > 0.9, "{:?}", stats);
+        // small ints are CPython's own singletons
+        let small = cache.int(py, 7);
+        assert!(small.as_ref(py).is(7.to_object(py).as_ref(py)));
+        assert_eq!(cache.stats(), stats);
+        let big = cache.int(py, 123_456);
+        assert!(big.as_ref(py).is(cache.int(py, 123_456).as_ref(py)));
+
+        bench.iter(|| {
+            black_box(run_cached_ints(py, &mut cache, black_box(&input)).unwrap());
+        });
+    });
+}
+
+#[bench]
+fn number_cache_repeated_ints_uncached(bench: &mut Bencher) {
+    Python::with_gil(|py| {
+        let input = repeated_ints();
+        bench.iter(|| {
+            black_box(run_uncached_ints(py, black_box(&input)).unwrap());
+        });
+    });
+}
+
+#[bench]
+fn number_cache_unique_ints(bench: &mut Bencher) {
+    // every value misses, this is the overhead when the cache doesn't pay off
+    Python::with_gil(|py| {
+        let input: Vec<i64> = (1_000..11_000).collect();
+        let mut cache = NumberCache::with_capacity(py, 256);
+        bench.iter(|| {
+            black_box(run_cached_ints(py, &mut cache, black_box(&input)).unwrap());
+        });
+    });
+}
+
+#[bench]
+fn number_cache_unique_ints_uncached(bench: &mut Bencher) {
+    Python::with_gil(|py| {
+        let input: Vec<i64> = (1_000..11_000).collect();
+        bench.iter(|| {
+            black_box(run_uncached_ints(py, black_box(&input)).unwrap());
+        });
+    });
+}
+
+#[bench]
+fn number_cache_repeated_floats(bench: &mut Bencher) {
+    Python::with_gil(|py| {
+        let input = repeated_floats();
+        let mut cache = NumberCache::with_capacity(py, 256);
+        let list = run_cached_floats(py, &mut cache, &input).unwrap();
+        assert!(list.eq(run_uncached_floats(py, &input).unwrap()).unwrap());
+        assert!(cache.stats().hit_rate() > 0.9, "{:?}", cache.stats());
+
+        bench.iter(|| {
+            black_box(run_cached_floats(py, &mut cache, black_box(&input)).unwrap());
+        });
+    });
+}
+
+#[bench]
+fn number_cache_repeated_floats_uncached(bench: &mut Bencher) {
+    Python::with_gil(|py| {
+        let input = repeated_floats();
+        bench.iter(|| {
+            black_box(run_uncached_floats(py, black_box(&input)).unwrap());
+        });
+    });
+}
+
+///////////////////////// ascii and latin-1 strings
+
+// 100 strings like the ones in `instantiation_list`, padded out to `len`
+fn padded_strings(len: usize) -> Vec<String> {
+    (0..100).map(|i| format!("{:0>width$}", i, width = len)).collect()
+}
+
+fn run_new_strings<'py>(py: Python<'py>, strings: &[String], new: impl Fn(&str) -> &'py PyString) -> &'py PyList {
+    let items: Vec<&PyAny> = strings.iter().map(|s| new(s) as &PyAny).collect();
+    PyList::new(py, items)
+}
+
+fn bench_new_strings(bench: &mut Bencher, len: usize, new: for<'py> fn(Python<'py>, &str) -> &'py PyString) {
+    Python::with_gil(|py| {
+        let strings = padded_strings(len);
+        let expected = run_new_strings(py, &strings, |s| PyString::new(py, s));
+        assert!(run_new_strings(py, &strings, |s| new(py, s)).eq(expected).unwrap());
+
+        bench.iter(|| {
+            black_box(run_new_strings(py, black_box(&strings), |s| new(py, s)));
+        });
+    });
+}
+
+fn new_pystring<'py>(py: Python<'py>, s: &str) -> &'py PyString {
+    PyString::new(py, s)
+}
+
+fn new_ascii<'py>(py: Python<'py>, s: &str) -> &'py PyString {
+    new_ascii_string(py, s.as_bytes(), Validation::Strict).unwrap()
+}
+
+fn new_latin1<'py>(py: Python<'py>, s: &str) -> &'py PyString {
+    new_latin1_string(py, s.as_bytes()).unwrap()
+}
+
+#[bench]
+fn new_string_short_pystring(bench: &mut Bencher) {
+    bench_new_strings(bench, 2, new_pystring);
+}
+
+#[bench]
+fn new_string_short_ascii(bench: &mut Bencher) {
+    bench_new_strings(bench, 2, new_ascii);
+}
+
+#[bench]
+fn new_string_short_latin1(bench: &mut Bencher) {
+    bench_new_strings(bench, 2, new_latin1);
+}
+
+#[bench]
+fn new_string_64_pystring(bench: &mut Bencher) {
+    bench_new_strings(bench, 64, new_pystring);
+}
+
+#[bench]
+fn new_string_64_ascii(bench: &mut Bencher) {
+    bench_new_strings(bench, 64, new_ascii);
+}
+
+#[bench]
+fn new_string_64_latin1(bench: &mut Bencher) {
+    bench_new_strings(bench, 64, new_latin1);
+}
+
+#[bench]
+fn new_string_1k_pystring(bench: &mut Bencher) {
+    bench_new_strings(bench, 1024, new_pystring);
+}
+
+#[bench]
+fn new_string_1k_ascii(bench: &mut Bencher) {
+    bench_new_strings(bench, 1024, new_ascii);
+}
+
+#[bench]
+fn new_string_1k_latin1(bench: &mut Bencher) {
+    bench_new_strings(bench, 1024, new_latin1);
+}
+
+#[bench]
+fn new_string_validation(bench: &mut Bencher) {
+    Python::with_gil(|py| {
+        let data = b"caf\xe9 au lait";
+        let err = new_ascii_string(py, data, Validation::Strict).unwrap_err();
+        assert_eq!(err.to_string(), "ValueError: byte 0xe9 at index 3 is not ASCII");
+        let replaced = new_ascii_string(py, data, Validation::Replace).unwrap();
+        assert_eq!(replaced.to_str().unwrap(), "caf? au lait");
+        assert_eq!(borrow_str(replaced).unwrap().1, StrPath::CompactAscii);
+
+        // latin-1 bytes are code points, not UTF-8
+        let latin1 = new_latin1_string(py, data).unwrap();
+        assert!(latin1.eq(PyString::new(py, "café au lait")).unwrap());
+        // all-ASCII latin-1 input must still end up as a compact ASCII string
+        let ascii = new_latin1_string(py, b"cafe").unwrap();
+        assert_eq!(borrow_str(ascii).unwrap().1, StrPath::CompactAscii);
+        assert_eq!(new_latin1_string(py, b"").unwrap().len().unwrap(), 0);
+
+        bench.iter(|| {
+            black_box(new_ascii_string(py, black_box(data), Validation::Replace).unwrap());
+        });
+    });
+}
+
+#[bench]
+fn new_string_latin1_non_ascii(bench: &mut Bencher) {
+    Python::with_gil(|py| {
+        let data = b"caf\xe9 au lait";
+        bench.iter(|| {
+            black_box(new_latin1_string(py, black_box(data)).unwrap());
+        });
+    });
+}
+
+#[bench]
+fn new_string_latin1_non_ascii_pystring(bench: &mut Bencher) {
+    Python::with_gil(|py| {
+        bench.iter(|| {
+            black_box(PyString::new(py, black_box("café au lait")));
+        });
+    });
+}
+
+///////////////////////// bytes and str builders
+
+// CSV-ish rows, `non_ascii` puts a non-ASCII character in the last row
+fn csv_rows(count: usize, non_ascii: bool) -> Vec<String> {
+    (0..count)
+        .map(|i| match non_ascii && i == count - 1 {
+            true => format!("{},valué_{},{}\n", i, i, i * 2),
+            false => format!("{},value_{},{}\n", i, i, i * 2),
+        })
+        .collect()
+}
+
+fn run_str_builder<'py>(py: Python<'py>, rows: &[String]) -> PyResult<&'py PyString> {
+    let mut builder = PyStringBuilder::with_capacity(py, 64)?;
+    for row in rows {
+        builder.push(py, row)?;
+    }
+    builder.get(py)
+}
+
+fn run_str_string<'py>(py: Python<'py>, rows: &[String]) -> &'py PyString {
+    let mut s = String::with_capacity(64);
+    for row in rows {
+        s.push_str(row);
+    }
+    PyString::new(py, &s)
+}
+
+fn run_bytes_builder<'py>(py: Python<'py>, rows: &[String]) -> PyResult<&'py PyBytes> {
+    let mut builder = PyBytesBuilder::with_capacity(py, 64)?;
+    for row in rows {
+        builder.push(py, row.as_bytes())?;
+    }
+    builder.get(py)
+}
+
+fn run_bytes_vec<'py>(py: Python<'py>, rows: &[String]) -> &'py PyBytes {
+    let mut vec = Vec::with_capacity(64);
+    for row in rows {
+        vec.extend_from_slice(row.as_bytes());
+    }
+    PyBytes::new(py, &vec)
+}
+
+#[bench]
+fn str_builder_rows(bench: &mut Bencher) {
+    Python::with_gil(|py| -> PyResult<()> {
+        let rows = csv_rows(500, false);
+        let built = run_str_builder(py, &rows)?;
+        assert!(built.eq(run_str_string(py, &rows))?);
+        assert_eq!(borrow_str(built)?.1, StrPath::CompactAscii);
+        // the result must behave like any other str, e.g. as a dict key
+        let dict = PyDict::new(py);
+        dict.set_item(run_str_string(py, &rows), 1)?;
+        assert!(dict.contains(built)?);
+
+        assert_eq!(PyStringBuilder::with_capacity(py, 0)?.get(py)?.to_str()?, "");
+        let mut builder = PyStringBuilder::with_capacity(py, 0)?;
+        builder.push(py, "a")?;
+        builder.push(py, "")?;
+        builder.push(py, "bc")?;
+        assert_eq!(builder.len(), 3);
+        assert_eq!(builder.get(py)?.to_str()?, "abc");
+
+        bench.iter(|| {
+            black_box(run_str_builder(py, black_box(&rows)).unwrap());
+        });
+        Ok(())
+    }).unwrap();
+}
+
+#[bench]
+fn str_builder_rows_non_ascii(bench: &mut Bencher) {
+    Python::with_gil(|py| -> PyResult<()> {
+        let rows = csv_rows(500, true);
+        let built = run_str_builder(py, &rows)?;
+        assert!(built.eq(run_str_string(py, &rows))?);
+        assert_eq!(borrow_str(built)?.1, StrPath::Utf8);
+
+        bench.iter(|| {
+            black_box(run_str_builder(py, black_box(&rows)).unwrap());
+        });
+        Ok(())
+    }).unwrap();
+}
+
+#[bench]
+fn str_builder_rows_string(bench: &mut Bencher) {
+    Python::with_gil(|py| {
+        let rows = csv_rows(500, false);
+        bench.iter(|| {
+            black_box(run_str_string(py, black_box(&rows)));
+        });
+    });
+}
+
+#[bench]
+fn bytes_builder_rows(bench: &mut Bencher) {
+    Python::with_gil(|py| -> PyResult<()> {
+        let rows = csv_rows(500, false);
+        let built = run_bytes_builder(py, &rows)?;
+        assert_eq!(built.as_bytes(), run_bytes_vec(py, &rows).as_bytes());
+
+        assert_eq!(PyBytesBuilder::with_capacity(py, 0)?.get(py)?.as_bytes(), b"");
+        let mut builder = PyBytesBuilder::with_capacity(py, 0)?;
+        builder.push(py, b"\x00\xff")?;
+        builder.push(py, b"abc")?;
+        assert_eq!(builder.len(), 5);
+        assert_eq!(builder.get(py)?.as_bytes(), b"\x00\xffabc");
+
+        bench.iter(|| {
+            black_box(run_bytes_builder(py, black_box(&rows)).unwrap());
+        });
+        Ok(())
+    }).unwrap();
+}
+
+#[bench]
+fn bytes_builder_rows_vec(bench: &mut Bencher) {
+    Python::with_gil(|py| {
+        let rows = csv_rows(500, false);
+        bench.iter(|| {
+            black_box(run_bytes_vec(py, black_box(&rows)));
+        });
+    });
+}
+
+///////////////////////// row sets
+
+fn run_row_set_builder<'py>(py: Python<'py>, input: &[usize]) -> PyResult<&'py PyList> {
+    let schema = [ColumnType::Int, ColumnType::Str, ColumnType::Float];
+    let mut builder = RowSetBuilder::with_schema(py, input.len(), &schema)?;
+    for &i in input {
+        builder.push_i64(py, i as i64)?;
+        builder.push_str(py, "value")?;
+        builder.push_f64(py, i as f64 / 2.0)?;
+    }
+    builder.get(py)
+}
+
+fn run_row_set_builder_push_row<'py>(py: Python<'py>, input: &[usize]) -> PyResult<&'py PyList> {
+    let mut builder = RowSetBuilder::with_capacity(py, input.len(), 3)?;
+    for &i in input {
+        builder.push_row(py, [(i as i64).to_object(py), "value".to_object(py), (i as f64 / 2.0).to_object(py)])?;
+    }
+    builder.get(py)
+}
+
+fn run_row_set_vec<'py>(py: Python<'py>, input: &[usize]) -> &'py PyList {
+    let mut rows: Vec<Vec<PyObject>> = Vec::with_capacity(input.len());
+    for &i in input {
+        rows.push(vec![(i as i64).to_object(py), "value".to_object(py), (i as f64 / 2.0).to_object(py)]);
+    }
+    PyList::new(py, rows.into_iter().map(|row| PyTuple::new(py, row)))
+}
+
+#[bench]
+fn row_set_builder(bench: &mut Bencher) {
+    Python::with_gil(|py| -> PyResult<()> {
+        let vec_5 = vec![0, 1, 2, 3, 4];
+        assert!(run_row_set_builder(py, &vec_5)?.eq(run_row_set_vec(py, &vec_5))?);
+
+        let vec_500: Vec<usize> = (0..500).collect();
+        bench.iter(|| {
+            black_box(run_row_set_builder(py, black_box(&vec_500)).unwrap());
+        });
+        Ok(())
+    }).unwrap();
+}
+
+#[bench]
+fn row_set_builder_push_row(bench: &mut Bencher) {
+    Python::with_gil(|py| -> PyResult<()> {
+        let vec_5 = vec![0, 1, 2, 3, 4];
+        assert!(run_row_set_builder_push_row(py, &vec_5)?.eq(run_row_set_vec(py, &vec_5))?);
+
+        let vec_500: Vec<usize> = (0..500).collect();
+        bench.iter(|| {
+            black_box(run_row_set_builder_push_row(py, black_box(&vec_500)).unwrap());
+        });
+        Ok(())
+    }).unwrap();
+}
+
+#[bench]
+fn row_set_vec(bench: &mut Bencher) {
+    Python::with_gil(|py| {
+        let vec_500: Vec<usize> = (0..500).collect();
+        bench.iter(|| {
+            black_box(run_row_set_vec(py, black_box(&vec_500)));
+        });
+    });
+}
+
+#[bench]
+fn row_set_partial_rows(bench: &mut Bencher) {
+    Python::with_gil(|py| -> PyResult<()> {
+        let mut builder = RowSetBuilder::with_capacity(py, 3, 2)?;
+        builder.push_row(py, [1, 2])?;
+        let err = builder.push_row(py, [3]).unwrap_err();
+        assert_eq!(err.to_string(), "ValueError: row has 1 items, expected 2");
+        let err = builder.push_row(py, [3, 4, 5]).unwrap_err();
+        assert_eq!(err.to_string(), "ValueError: row has more than 2 items");
+        // failed rows leave nothing behind
+        assert_eq!(builder.len(), 1);
+
+        builder.push_i64(py, 3)?;
+        let err = builder.push_row(py, [5, 6]).unwrap_err();
+        assert_eq!(err.to_string(), "ValueError: push_row() called with a partial row in progress");
+        builder.push_none(py)?;
+        builder.push_bool(py, true)?;
+        assert_eq!(builder.len(), 2);
+
+        let err = builder.get(py).unwrap_err();
+        assert_eq!(err.to_string(), "ValueError: row set has a partial row");
+        assert!(PyErr::take(py).is_none());
+
+        let mut builder = RowSetBuilder::with_capacity(py, 2, 2)?;
+        builder.push_row(py, ["a", "b"])?;
+        builder.push_str(py, "c")?;
+        builder.discard_row();
+        builder.push_row(py, ["d", "e"])?;
+        let err = builder.push_str(py, "f").unwrap_err();
+        assert_eq!(err.to_string(), "ValueError: push() exceeded row capacity");
+        assert_eq!(builder.get(py)?.to_string(), "[('a', 'b'), ('d', 'e')]");
+
+        let mut builder = RowSetBuilder::with_capacity(py, 5, 2)?;
+        builder.push_row(py, [1, 2])?;
+        builder.push_i64(py, 3)?;
+        // the partial row is dropped along with the unfilled capacity
+        assert_eq!(builder.get_incomplete(py).to_string(), "[(1, 2)]");
+
+        let err = RowSetBuilder::with_capacity(py, 5, 0).err().unwrap();
+        assert_eq!(err.to_string(), "ValueError: row set must have at least one column");
+
+        let rows = vec![vec![1, 2, 3], vec![4, 5]];
+        bench.iter(|| {
+            let mut builder = RowSetBuilder::with_capacity(py, 2, 3).unwrap();
+            for row in black_box(&rows) {
+                let _ = builder.push_row(py, row);
+            }
+            black_box(builder.get_incomplete(py));
+        });
+        Ok(())
+    }).unwrap();
+}
+
+#[bench]
+fn row_set_schema(bench: &mut Bencher) {
+    Python::with_gil(|py| -> PyResult<()> {
+        let schema = [ColumnType::Int, ColumnType::Bool, ColumnType::Any];
+        let mut builder = RowSetBuilder::with_schema(py, 3, &schema)?;
+        assert_eq!(builder.schema(), &schema);
+
+        // the typed pushes are checked against the column before the object is built
+        let err = builder.push_f64(py, 1.5).unwrap_err();
+        assert_eq!(err.to_string(), "TypeError: column 0 expects int, got float");
+        // bool is a subclass of int but isn't accepted in an int column
+        let err = builder.push(py, true).unwrap_err();
+        assert_eq!(err.to_string(), "TypeError: column 0 expects int, got bool");
+        builder.push_i64(py, 1)?;
+        let err = builder.push_i64(py, 1).unwrap_err();
+        assert_eq!(err.to_string(), "TypeError: column 1 expects bool, got int");
+        // a failed push leaves the partial row as it was
+        builder.push_bool(py, false)?;
+        builder.push_str(py, "any")?;
+
+        // None is accepted in any column
+        builder.push_row(py, [py.None(), py.None(), py.None()])?;
+        let err = builder.push_row(py, [2.to_object(py), "x".to_object(py), 3.to_object(py)]).unwrap_err();
+        assert_eq!(err.to_string(), "TypeError: column 1 expects bool, got str");
+        assert_eq!(builder.len(), 2);
+        builder.push_row(py, [2.to_object(py), true.to_object(py), 3.5.to_object(py)])?;
+        assert_eq!(builder.get(py)?.to_string(), "[(1, False, 'any'), (None, None, None), (2, True, 3.5)]");
+
+        let err = RowSetBuilder::with_schema(py, 5, &[]).err().unwrap();
+        assert_eq!(err.to_string(), "ValueError: row set must have at least one column");
+
+        let vec_500: Vec<usize> = (0..500).collect();
+        bench.iter(|| {
+            let mut builder = RowSetBuilder::with_schema(py, 500, &schema).unwrap();
+            for &i in black_box(&vec_500) {
+                let _ = builder.push_i64(py, i as i64);
+                let _ = builder.push_f64(py, i as f64);
+                builder.discard_row();
+            }
+            black_box(builder.get_incomplete(py));
+        });
+        Ok(())
+    }).unwrap();
+}
+
+///////////////////////// checkpoint and rollback
+
+// pushes `input`, rolling back every other item as if a union branch failed halfway through
+fn run_py_list_builder_rollback<'py>(py: Python<'py>, input: &[usize]) -> PyResult<&'py PyList> {
+    let mut list_builder = PyListBuilder::with_capacity(py, input.len())?;
+    for i in input {
+        let checkpoint = list_builder.checkpoint();
+        list_builder.push(py, get_value(i))?;
+        if i % 2 == 1 {
+            list_builder.rollback_to(checkpoint)?;
+            list_builder.push(py, get_value(i))?;
+        }
+    }
+    list_builder.get(py)
+}
+
+#[bench]
+fn py_list_builder_rollback(bench: &mut Bencher) {
+    Python::with_gil(|py| -> PyResult<()> {
+        let obj: PyObject = PyList::empty(py).into();
+        let start = obj.get_refcnt(py);
+
+        let mut list_builder = PyListBuilder::with_capacity(py, 4)?;
+        list_builder.push(py, &obj)?;
+        let checkpoint = list_builder.checkpoint();
+        list_builder.push(py, &obj)?;
+        list_builder.push(py, &obj)?;
+        assert_eq!(obj.get_refcnt(py), start + 3);
+        list_builder.rollback_to(checkpoint)?;
+        assert_eq!(obj.get_refcnt(py), start + 1);
+        // rolling back to where we already are is a no-op
+        list_builder.rollback_to(checkpoint)?;
+        assert_eq!(obj.get_refcnt(py), start + 1);
+
+        let ahead = {
+            list_builder.push(py, 1)?;
+            let ahead = list_builder.checkpoint();
+            list_builder.rollback_to(checkpoint)?;
+            ahead
+        };
+        let err = list_builder.rollback_to(ahead).unwrap_err();
+        assert_eq!(err.to_string(), "ValueError: checkpoint is ahead of the builder");
+        assert!(ahead > checkpoint);
+
+        list_builder.push(py, 2)?;
+        list_builder.push(py, 3)?;
+        list_builder.push(py, 4)?;
+        assert_eq!(list_builder.get(py)?.to_string(), "[[], 2, 3, 4]");
+
+        let mut list_builder = PyListBuilder::with_capacity(py, 3)?;
+        let empty: Checkpoint = list_builder.checkpoint();
+        list_builder.push(py, &obj)?;
+        list_builder.push(py, &obj)?;
+        list_builder.rollback_to(empty)?;
+        assert_eq!(obj.get_refcnt(py), start + 1);
+        assert_eq!(list_builder.get_incomplete(py).len(), 0);
+
+        let vec_5 = vec![0, 1, 2, 3, 4];
+        assert!(run_py_list_builder_rollback(py, &vec_5)?.eq(run_py_list_vec(py, &vec_5))?);
+
+        let vec_500: Vec<usize> = (0..500).collect();
+        bench.iter(|| {
+            black_box(run_py_list_builder_rollback(py, black_box(&vec_500)).unwrap());
+        });
+        Ok(())
+    }).unwrap();
+}
+
+#[bench]
+fn py_tuple_builder_rollback(bench: &mut Bencher) {
+    Python::with_gil(|py| -> PyResult<()> {
+        let obj: PyObject = PyList::empty(py).into();
+        let start = obj.get_refcnt(py);
+
+        let mut tuple_builder = PyTupleBuilder::with_capacity(py, 3)?;
+        let first_empty = tuple_builder.checkpoint();
+        tuple_builder.push(py, &obj)?;
+        let checkpoint = tuple_builder.checkpoint();
+        tuple_builder.push(py, &obj)?;
+        tuple_builder.push(py, &obj)?;
+        assert_eq!(obj.get_refcnt(py), start + 3);
+        tuple_builder.rollback_to(checkpoint)?;
+        assert_eq!(obj.get_refcnt(py), start + 1);
+
+        tuple_builder.push(py, 2)?;
+        tuple_builder.push(py, 3)?;
+        let current = tuple_builder.checkpoint();
+        tuple_builder.rollback_to(current)?;
+        assert_eq!(tuple_builder.get(py)?.to_string(), "([], 2, 3)");
+
+        let mut tuple_builder = PyTupleBuilder::with_capacity(py, 3)?;
+        let empty = tuple_builder.checkpoint();
+        tuple_builder.push(py, &obj)?;
+        tuple_builder.push(py, &obj)?;
+        // a checkpoint only applies to the builder it was taken from
+        let err = tuple_builder.rollback_to(first_empty).unwrap_err();
+        assert_eq!(err.to_string(), "ValueError: checkpoint is from a different builder");
+        assert_eq!(tuple_builder.len(), 2);
+        tuple_builder.rollback_to(empty)?;
+        drop(tuple_builder);
+        // only the reference held by the finished tuple above is left
+        assert_eq!(obj.get_refcnt(py), start + 1);
+
+        let vec_500: Vec<usize> = (0..500).collect();
+        bench.iter(|| {
+            let mut tuple_builder = PyTupleBuilder::with_capacity(py, vec_500.len()).unwrap();
+            for i in black_box(&vec_500) {
+                let checkpoint = tuple_builder.checkpoint();
+                tuple_builder.push(py, get_value(i)).unwrap();
+                if i % 2 == 1 {
+                    tuple_builder.rollback_to(checkpoint).unwrap();
+                    tuple_builder.push(py, get_value(i)).unwrap();
+                }
+            }
+            black_box(tuple_builder.get(py).unwrap());
+        });
+        Ok(())
+    }).unwrap();
+}
+
+///////////////////////// try_fill
+
+fn run_py_list_builder_try_fill<'py>(py: Python<'py>, input: &[usize]) -> PyResult<&'py PyList> {
+    let mut list_builder = PyListBuilder::with_capacity(py, input.len())?;
+    list_builder.try_fill(py, input.len(), |i| Ok(get_value(&input[i])))?;
+    list_builder.get(py)
+}
+
+fn item_or_fail(input: &[usize], fail_at: usize, i: usize) -> PyResult<usize> {
+    match input[i] == fail_at {
+        true => Err(pyo3::exceptions::PyTypeError::new_err("boom")),
+        false => Ok(get_value(&input[i])),
+    }
+}
+
+#[bench]
+fn py_list_builder_try_fill(bench: &mut Bencher) {
+    Python::with_gil(|py| -> PyResult<()> {
+        let vec_5 = vec![0, 1, 2, 3, 4];
+        assert!(run_py_list_builder_try_fill(py, &vec_5)?.eq(run_py_list_vec(py, &vec_5))?);
+
+        let mut list_builder = PyListBuilder::with_capacity(py, 8)?;
+        list_builder.push(py, "first")?;
+        assert_eq!((list_builder.len(), list_builder.remaining()), (1, 7));
+        let err = list_builder.try_fill(py, 5, |i| item_or_fail(&vec_5, 3, i)).unwrap_err();
+        assert_eq!(err.to_string(), "FillError: item 3: TypeError: boom");
+        assert!(err.is_instance_of::<FillError>(py));
+        assert!(err.is_instance_of::<pyo3::exceptions::PyValueError>(py));
+        let cause = err.cause(py).unwrap();
+        assert_eq!(cause.to_string(), "TypeError: boom");
+        // everything pushed by the failed fill is rolled back
+        assert_eq!((list_builder.len(), list_builder.remaining()), (1, 7));
+        // a cause raised by Python keeps its traceback
+        let err = list_builder.try_fill(py, 1, |_| py.eval("1 / 0", None, None)).unwrap_err();
+        assert_eq!(err.to_string(), "FillError: item 0: ZeroDivisionError: division by zero");
+        let cause = err.cause(py).unwrap();
+        assert!(!cause.value(py).getattr("__traceback__").unwrap().is_none());
+
+        let err = list_builder.try_fill(py, 8, |i| item_or_fail(&vec_5, 3, i)).unwrap_err();
+        assert_eq!(err.to_string(), "ValueError: try_fill() of 8 items exceeds remaining capacity 7");
+        assert!(!err.is_instance_of::<FillError>(py));
+
+        list_builder.try_fill(py, 7, Ok)?;
+        assert_eq!(list_builder.remaining(), 0);
+        assert_eq!(list_builder.get(py)?.to_string(), "['first', 0, 1, 2, 3, 4, 5, 6]");
+        assert!(PyErr::take(py).is_none());
+
+        let vec_500: Vec<usize> = (0..500).collect();
+        bench.iter(|| {
+            black_box(run_py_list_builder_try_fill(py, black_box(&vec_500)).unwrap());
+        });
+        Ok(())
+    }).unwrap();
+}
+
+#[bench]
+fn py_tuple_builder_try_fill(bench: &mut Bencher) {
+    Python::with_gil(|py| -> PyResult<()> {
+        let vec_5 = vec![0, 1, 2, 3, 4];
+        let mut tuple_builder = PyTupleBuilder::with_capacity(py, 5)?;
+        let err = tuple_builder.try_fill(py, 5, |i| item_or_fail(&vec_5, 0, i)).unwrap_err();
+        assert_eq!(err.to_string(), "FillError: item 0: TypeError: boom");
+        assert_eq!((tuple_builder.len(), tuple_builder.remaining()), (0, 5));
+        assert!(tuple_builder.is_empty());
+
+        tuple_builder.try_fill(py, 5, |i| Ok(vec_5[i]))?;
+        assert!(tuple_builder.get(py)?.eq(run_py_tuple_vec(py, &vec_5))?);
+
+        let vec_500: Vec<usize> = (0..500).collect();
+        bench.iter(|| {
+            let mut tuple_builder = PyTupleBuilder::with_capacity(py, vec_500.len()).unwrap();
+            let input = black_box(&vec_500);
+            tuple_builder.try_fill(py, input.len(), |i| Ok(get_value(&input[i]))).unwrap();
+            black_box(tuple_builder.get(py).unwrap());
+        });
+        Ok(())
+    }).unwrap();
+}
+
+///////////////////////// typed list builder
+
+fn run_typed_list_builder<'py, T: TypedItem + Copy>(py: Python<'py>, input: &[T]) -> PyResult<&'py PyList> {
+    let mut list_builder = TypedListBuilder::with_capacity(py, input.len())?;
+    for &item in input {
+        list_builder.push(py, item)?;
+    }
+    list_builder.get(py)
+}
+
+fn run_untyped_list_builder<'py, T: ToPyObject>(py: Python<'py>, input: &[T]) -> PyResult<&'py PyList> {
+    let mut list_builder = PyListBuilder::with_capacity(py, input.len())?;
+    for item in input {
+        list_builder.push(py, item)?;
+    }
+    list_builder.get(py)
+}
+
+fn bench_typed_list<T: TypedItem + Copy>(bench: &mut Bencher, input: fn(usize) -> T) {
+    Python::with_gil(|py| {
+        let input: Vec<T> = (0..500).map(input).collect();
+        let typed = run_typed_list_builder(py, &input).unwrap();
+        assert!(typed.eq(run_untyped_list_builder(py, &input).unwrap()).unwrap());
+
+        bench.iter(|| {
+            black_box(run_typed_list_builder(py, black_box(&input)).unwrap());
+        });
+    });
+}
+
+fn bench_untyped_list<T: ToPyObject>(bench: &mut Bencher, input: fn(usize) -> T) {
+    Python::with_gil(|py| {
+        let input: Vec<T> = (0..500).map(input).collect();
+        bench.iter(|| {
+            black_box(run_untyped_list_builder(py, black_box(&input)).unwrap());
+        });
+    });
+}
+
+fn typed_i64(i: usize) -> i64 {
+    i as i64 * 1000
+}
+
+fn typed_f64(i: usize) -> f64 {
+    i as f64 / 3.0
+}
+
+fn typed_bool(i: usize) -> bool {
+    i.is_multiple_of(3)
+}
+
+fn typed_str(i: usize) -> &'static str {
+    ["apple", "banana", "cherry", "damson", "elderberry"][i % 5]
+}
+
+#[bench]
+fn typed_list_i64(bench: &mut Bencher) {
+    bench_typed_list(bench, typed_i64);
+}
+
+#[bench]
+fn typed_list_i64_untyped(bench: &mut Bencher) {
+    bench_untyped_list(bench, typed_i64);
+}
+
+#[bench]
+fn typed_list_f64(bench: &mut Bencher) {
+    bench_typed_list(bench, typed_f64);
+}
+
+#[bench]
+fn typed_list_f64_untyped(bench: &mut Bencher) {
+    bench_untyped_list(bench, typed_f64);
+}
+
+#[bench]
+fn typed_list_bool(bench: &mut Bencher) {
+    Python::with_gil(|py| -> PyResult<()> {
+        let list = run_typed_list_builder(py, &[true, false])?;
+        // the singletons themselves, not copies
+        assert!(list.get_item(0)?.is(PyBool::new(py, true)));
+        assert!(list.get_item(1)?.is(PyBool::new(py, false)));
+        Ok(())
+    }).unwrap();
+    bench_typed_list(bench, typed_bool);
+}
+
+#[bench]
+fn typed_list_bool_untyped(bench: &mut Bencher) {
+    bench_untyped_list(bench, typed_bool);
+}
+
+#[bench]
+fn typed_list_str(bench: &mut Bencher) {
+    Python::with_gil(|py| -> PyResult<()> {
+        let mut list_builder = TypedListBuilder::<&str>::with_capacity(py, 2)?;
+        list_builder.push(py, "a")?;
+        assert_eq!((list_builder.len(), list_builder.remaining()), (1, 1));
+        list_builder.push(py, "é")?;
+        let err = list_builder.push(py, "c").unwrap_err();
+        assert_eq!(err.to_string(), "ValueError: push() exceeded list capacity");
+        assert_eq!(list_builder.get(py)?.to_string(), "['a', 'é']");
+
+        let mut list_builder = TypedListBuilder::<String>::with_capacity(py, 3)?;
+        list_builder.push(py, "owned".to_string())?;
+        assert_eq!(list_builder.get_incomplete(py).to_string(), "['owned']");
+        Ok(())
+    }).unwrap();
+    bench_typed_list(bench, typed_str);
+}
+
+#[bench]
+fn typed_list_str_untyped(bench: &mut Bencher) {
+    bench_untyped_list(bench, typed_str);
+}
+
+struct Point(i64, i64);
+
+impl ToPyObject for Point {
+    fn to_object(&self, py: Python<'_>) -> PyObject {
+        (self.0, self.1).to_object(py)
+    }
+}
+
+// no fast path, pushed through `to_object`
+impl TypedItem for Point {}
+
+#[bench]
+fn typed_list_opt_in(bench: &mut Bencher) {
+    Python::with_gil(|py| -> PyResult<()> {
+        let mut list_builder = TypedListBuilder::<i16>::with_capacity(py, 2)?;
+        list_builder.push(py, -3)?;
+        list_builder.push(py, i16::MAX)?;
+        assert_eq!(list_builder.get(py)?.to_string(), "[-3, 32767]");
+
+        let dict: Py<PyDict> = [("a", 1)].into_py_dict(py).into();
+        let start = dict.get_refcnt(py);
+        let mut list_builder = TypedListBuilder::<Py<PyDict>>::with_capacity(py, 1)?;
+        list_builder.push(py, dict.clone_ref(py))?;
+        // the reference pushed is the one moved in
+        assert_eq!(dict.get_refcnt(py), start + 1);
+        assert_eq!(list_builder.get(py)?.to_string(), "[{'a': 1}]");
+
+        let mut list_builder = TypedListBuilder::<Point>::with_capacity(py, 1)?;
+        list_builder.push(py, Point(1, 2))?;
+        assert_eq!(list_builder.get(py)?.to_string(), "[(1, 2)]");
+
+        let vec_500: Vec<i64> = (0..500).collect();
+        bench.iter(|| {
+            let mut list_builder = TypedListBuilder::<Point>::with_capacity(py, vec_500.len()).unwrap();
+            for &i in black_box(&vec_500) {
+                list_builder.push(py, Point(i, -i)).unwrap();
+            }
+            black_box(list_builder.get(py).unwrap());
+        });
+        Ok(())
+    }).unwrap();
+}
+
+///////////////////////// in place truncation
+
+fn run_py_list_builder_truncated<'py>(py: Python<'py>, break_at: usize, input: &[usize]) -> PyResult<&'py PyList> {
+    let mut list_builder = PyListBuilder::with_capacity(py, input.len())?;
+    for i in input {
+        list_builder.push(py , get_value(i))?;
+        if i >= &break_at {
+            break;
+        }
+    }
+    list_builder.get_truncated(py)
+}
+
+fn run_py_tuple_builder_truncated<'py>(py: Python<'py>, break_at: usize, input: &[usize]) -> PyResult<&'py PyTuple> {
+    let mut tuple_builder = PyTupleBuilder::with_capacity(py, input.len())?;
+    for i in input {
+        tuple_builder.push(py, get_value(i))?;
+        if i >= &break_at {
+            break;
+        }
+    }
+    tuple_builder.get_truncated(py)
+}
+
+#[bench]
+fn py_list_truncated_builder(bench: &mut Bencher) {
+    Python::with_gil(|py| -> PyResult<()> {
+        let vec_5 = vec![0, 1, 2, 3, 4];
+        let list_3 = run_py_list_builder_truncated(py, 3, &vec_5)?;
+        assert!(list_3.eq(run_py_list_vec_incomplete(py, 3, &vec_5))?);
+        assert!(run_py_list_builder_truncated(py, 10, &vec_5)?.eq(run_py_list_vec(py, &vec_5))?);
+        assert_eq!(PyListBuilder::with_capacity(py, 5)?.get_truncated(py)?.len(), 0);
+
+        let obj: PyObject = PyList::empty(py).into();
+        let start = obj.get_refcnt(py);
+        let mut list_builder = PyListBuilder::with_capacity(py, 5)?;
+        list_builder.push(py, &obj)?;
+        list_builder.push(py, &obj)?;
+        let list = list_builder.get_truncated(py)?;
+        assert_eq!(list.len(), 2);
+        // the items were kept rather than copied, so no extra references
+        assert_eq!(obj.get_refcnt(py), start + 2);
+
+        let vec_500: Vec<usize> = (0..500).collect();
+        bench.iter(|| {
+            let list_400 = run_py_list_builder_truncated(py, black_box(400), black_box(&vec_500)).unwrap();
+            black_box(list_400);
+        });
+        Ok(())
+    }).unwrap();
+}
+
+#[bench]
+fn py_tuple_truncated_builder(bench: &mut Bencher) {
+    Python::with_gil(|py| -> PyResult<()> {
+        let vec_5 = vec![0, 1, 2, 3, 4];
+        let tuple_3 = run_py_tuple_builder_truncated(py, 3, &vec_5)?;
+        assert!(tuple_3.eq(run_py_tuple_vec_incomplete(py, 3, &vec_5))?);
+        assert!(run_py_tuple_builder_truncated(py, 10, &vec_5)?.eq(run_py_tuple_vec(py, &vec_5))?);
+        assert_eq!(PyTupleBuilder::with_capacity(py, 5)?.get_truncated(py)?.len(), 0);
+
+        let obj: PyObject = PyList::empty(py).into();
+        let start = obj.get_refcnt(py);
+        let mut tuple_builder = PyTupleBuilder::with_capacity(py, 5)?;
+        tuple_builder.push(py, &obj)?;
+        tuple_builder.push(py, &obj)?;
+        let tuple = tuple_builder.get_truncated(py)?;
+        assert_eq!(tuple.len(), 2);
+        assert_eq!(obj.get_refcnt(py), start + 2);
+        // still usable as an ordinary tuple, e.g. hashed as a dict key
+        let dict = PyDict::new(py);
+        dict.set_item(PyTuple::new(py, [1, 2]), "x")?;
+        let mut tuple_builder = PyTupleBuilder::with_capacity(py, 3)?;
+        tuple_builder.push(py, 1)?;
+        tuple_builder.push(py, 2)?;
+        assert!(dict.contains(tuple_builder.get_truncated(py)?)?);
+
+        let vec_500: Vec<usize> = (0..500).collect();
+        bench.iter(|| {
+            let tuple_400 = run_py_tuple_builder_truncated(py, black_box(400), black_box(&vec_500)).unwrap();
+            black_box(tuple_400);
+        });
+        Ok(())
+    }).unwrap();
+}
+
+///////////////////////// records
+
+const RECORD_FIELDS: [&str; 3] = ["id", "name", "score"];
+
+fn run_record_builder<'py>(py: Python<'py>, record_type: &RecordType, input: &[usize]) -> PyResult<&'py PyList> {
+    let mut list_builder = PyListBuilder::with_capacity(py, input.len())?;
+    for &i in input {
+        let mut record = record_type.builder(py)?;
+        record.push(py, "id", i)?;
+        record.push(py, "name", "value")?;
+        record.push(py, "score", i as f64 / 2.0)?;
+        list_builder.push(py, record.get(py)?)?;
+    }
+    list_builder.get(py)
+}
+
+fn run_record_call<'py>(py: Python<'py>, class: &PyType, input: &[usize]) -> PyResult<&'py PyList> {
+    let mut list_builder = PyListBuilder::with_capacity(py, input.len())?;
+    for &i in input {
+        list_builder.push(py, class.call1((i, "value", i as f64 / 2.0))?)?;
+    }
+    list_builder.get(py)
+}
+
+#[bench]
+fn record_namedtuple_builder(bench: &mut Bencher) {
+    Python::with_gil(|py| -> PyResult<()> {
+        let record_type = RecordType::namedtuple(py, "Row", &RECORD_FIELDS)?;
+        assert_eq!(record_type.kind(), RecordKind::NamedTuple);
+        let class = record_type.class(py);
+
+        let mut record = record_type.builder(py)?;
+        // fields can be pushed in any order
+        record.push(py, "score", 1.5)?;
+        record.push(py, "id", 1)?;
+        let err = record.push(py, "id", 2).unwrap_err();
+        assert_eq!(err.to_string(), "ValueError: field 'id' already set");
+        let err = record.push(py, "nope", 2).unwrap_err();
+        assert_eq!(err.to_string(), "ValueError: unknown field 'nope'");
+        record.push_index(py, 1, "x")?;
+        let row = record.get(py)?;
+        assert!(row.is_instance(class)?);
+        assert_eq!(row.repr()?.to_str()?, "Row(id=1, name='x', score=1.5)");
+        assert_eq!(row.getattr("name")?.extract::<&str>()?, "x");
+        assert!(row.eq(class.call1((1, "x", 1.5))?)?);
+
+        let mut record = record_type.builder(py)?;
+        record.push(py, "id", 1)?;
+        let err = record.get(py).unwrap_err();
+        assert_eq!(err.to_string(), "ValueError: missing field 'name'");
+
+        let vec_5 = vec![0, 1, 2, 3, 4];
+        assert!(run_record_builder(py, &record_type, &vec_5)?.eq(run_record_call(py, class, &vec_5)?)?);
+
+        let vec_500: Vec<usize> = (0..500).collect();
+        bench.iter(|| {
+            black_box(run_record_builder(py, &record_type, black_box(&vec_500)).unwrap());
+        });
+        Ok(())
+    }).unwrap();
+}
+
+#[bench]
+fn record_namedtuple_call(bench: &mut Bencher) {
+    Python::with_gil(|py| -> PyResult<()> {
+        let record_type = RecordType::namedtuple(py, "Row", &RECORD_FIELDS)?;
+        let class = record_type.class(py);
+        let vec_500: Vec<usize> = (0..500).collect();
+        bench.iter(|| {
+            black_box(run_record_call(py, class, black_box(&vec_500)).unwrap());
+        });
+        Ok(())
+    }).unwrap();
+}
+
+#[bench]
+fn record_struct_sequence_builder(bench: &mut Bencher) {
+    Python::with_gil(|py| -> PyResult<()> {
+        let record_type = RecordType::struct_sequence(py, "rust_bench.Row", &RECORD_FIELDS)?;
+        assert_eq!(record_type.kind(), RecordKind::StructSequence);
+        assert_eq!(record_type.fields(), RECORD_FIELDS);
+        let class = record_type.class(py);
+
+        let mut record = record_type.builder(py)?;
+        record.push(py, "id", 1)?;
+        record.push(py, "name", "x")?;
+        record.push(py, "score", 1.5)?;
+        let row = record.get(py)?;
+        assert!(row.is_instance(class)?);
+        assert_eq!(row.repr()?.to_str()?, "rust_bench.Row(id=1, name='x', score=1.5)");
+        assert_eq!(row.getattr("score")?.extract::<f64>()?, 1.5);
+        assert!(row.eq(PyTuple::new(py, [1.to_object(py), "x".to_object(py), 1.5.to_object(py)]))?);
+        // struct sequences are built from a single sequence argument
+        assert!(row.eq(class.call1(((1, "x", 1.5),))?)?);
+
+        // an incomplete record is dropped without touching the unset fields
+        let mut record = record_type.builder(py)?;
+        record.push(py, "name", "y")?;
+        drop(record);
+
+        let err = RecordType::struct_sequence(py, "bad\0name", &RECORD_FIELDS).err().unwrap();
+        assert_eq!(err.to_string(), "ValueError: \"bad\\0name\" contains a null byte");
+
+        let vec_500: Vec<usize> = (0..500).collect();
+        bench.iter(|| {
+            black_box(run_record_builder(py, &record_type, black_box(&vec_500)).unwrap());
+        });
+        Ok(())
+    }).unwrap();
+}
+
+///////////////////////// instances
+
+// `(Point, NoInit, Slotted)`
+fn instance_classes(py: Python<'_>) -> PyResult<(&PyType, &PyType, &PyType)> {
+    let code = r#"
+from dataclasses import dataclass
+
+@dataclass
+class Point:
+    x: int
+    y: int
+    label: str
+
+class NoInit:
+    def __init__(self):
+        raise RuntimeError('__init__ called')
+
+class Slotted:
+    __slots__ = ('x',)
+"#;
+    let module = PyModule::from_code(py, code, "instances.py", "instances")?;
+    Ok((
+        module.getattr("Point")?.downcast()?,
+        module.getattr("NoInit")?.downcast()?,
+        module.getattr("Slotted")?.downcast()?,
+    ))
+}
+
+fn run_instance_builder<'py>(py: Python<'py>, class: &PyType, input: &[usize]) -> PyResult<&'py PyList> {
+    let mut list_builder = PyListBuilder::with_capacity(py, input.len())?;
+    for &i in input {
+        let mut instance = InstanceBuilder::with_capacity(py, class, 3)?;
+        instance.set_attr(py, "x", i)?;
+        instance.set_attr(py, "y", i * 2)?;
+        instance.set_attr(py, "label", "value")?;
+        list_builder.push(py, instance.get(py)?)?;
+    }
+    list_builder.get(py)
+}
+
+fn run_instance_call<'py>(py: Python<'py>, class: &PyType, input: &[usize]) -> PyResult<&'py PyList> {
+    let mut list_builder = PyListBuilder::with_capacity(py, input.len())?;
+    for &i in input {
+        let kwargs = PyDict::new(py);
+        kwargs.set_item("x", i)?;
+        kwargs.set_item("y", i * 2)?;
+        kwargs.set_item("label", "value")?;
+        list_builder.push(py, class.call((), Some(kwargs))?)?;
+    }
+    list_builder.get(py)
+}
+
+#[bench]
+fn instance_builder(bench: &mut Bencher) {
+    Python::with_gil(|py| -> PyResult<()> {
+        let (point, no_init, slotted) = instance_classes(py)?;
+        let vec_5 = vec![0, 1, 2, 3, 4];
+        let built = run_instance_builder(py, point, &vec_5)?;
+        assert!(built.eq(run_instance_call(py, point, &vec_5)?)?);
+        let first = built.get_item(1)?;
+        assert!(first.is_instance(point)?);
+        assert_eq!(first.repr()?.to_str()?, "Point(x=1, y=2, label='value')");
+        assert_eq!(first.getattr("__dict__")?.len()?, 3);
+
+        let mut instance = InstanceBuilder::with_capacity(py, no_init, 1)?;
+        instance.set_attr(py, "a", 1)?;
+        let obj = instance.get(py)?;
+        assert!(obj.is_instance(no_init)?);
+        assert_eq!(obj.getattr("a")?.extract::<i64>()?, 1);
+
+        let err = InstanceBuilder::with_capacity(py, slotted, 1)?.get(py).unwrap_err();
+        assert_eq!(err.to_string(), "AttributeError: This object has no __dict__");
+
+        let err = InstanceBuilder::with_capacity(py, py.get_type::<PyInt>(), 0)?.get(py).unwrap_err();
+        assert_eq!(err.to_string(), "TypeError: object.__new__(int) is not safe, use int.__new__()");
+
+        let vec_500: Vec<usize> = (0..500).collect();
+        bench.iter(|| {
+            black_box(run_instance_builder(py, point, black_box(&vec_500)).unwrap());
+        });
+        Ok(())
+    }).unwrap();
+}
+
+#[bench]
+fn instance_call_kwargs(bench: &mut Bencher) {
+    Python::with_gil(|py| -> PyResult<()> {
+        let (point, _, _) = instance_classes(py)?;
+        let vec_500: Vec<usize> = (0..500).collect();
+        bench.iter(|| {
+            black_box(run_instance_call(py, point, black_box(&vec_500)).unwrap());
+        });
+        Ok(())
+    }).unwrap();
+}
@@ -0,0 +1,49 @@
+use std::slice;
+
+use pyo3::buffer::{Element, PyBuffer};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+// a C-contiguous buffer of `T`, e.g. `array.array('q')` for `i64` or `bytes` for `u8`
+pub struct BufferSlice<T: Element> {
+    buffer: PyBuffer<T>,
+}
+
+impl<T: Element> BufferSlice<T> {
+    pub fn new(obj: &PyAny) -> PyResult<Self> {
+        // checks the buffer's format and item size match `T`
+        let buffer = PyBuffer::<T>::get(obj)?;
+        if !buffer.is_c_contiguous() {
+            return Err(PyValueError::new_err("buffer is not C-contiguous"));
+        }
+        Ok(Self { buffer })
+    }
+
+    pub fn len(&self) -> usize {
+        self.buffer.item_count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn readonly(&self) -> bool {
+        self.buffer.readonly()
+    }
+
+    /// # Safety
+    ///
+    /// The exporter can't be resized while we hold the buffer, but writable buffers (e.g.
+    /// `array.array` or `bytearray`) can still be written to by Python code. The caller must make
+    /// sure no Python code runs which modifies the data while the slice is alive.
+    pub unsafe fn as_slice<'a>(&'a self, _py: Python<'a>) -> &'a [T] {
+        if self.is_empty() {
+            return &[];
+        }
+        slice::from_raw_parts(self.buffer.buf_ptr() as *const T, self.len())
+    }
+
+    pub fn release(self, py: Python) {
+        self.buffer.release(py)
+    }
+}
@@ -0,0 +1,228 @@
+use std::os::raw::c_char;
+use std::slice;
+use std::str;
+
+use pyo3::prelude::*;
+use pyo3::{ffi, AsPyPointer};
+
+use crate::string::compact_ascii_str;
+
+// deliberately not a `PyErr`: building one means creating a Python exception object, which is
+// most of the cost when extraction fails
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExtractError {
+    WrongType,
+    Overflow,
+    InvalidUtf8,
+    // right type for lax mode, but the value can't be coerced, e.g. `1.5` or `"foo"` as an int
+    InvalidValue,
+}
+
+pub type ExtractResult<T> = Result<T, ExtractError>;
+
+pub fn try_extract_str(obj: &PyAny) -> ExtractResult<&str> {
+    let ptr = obj.as_ptr();
+    unsafe {
+        if ffi::PyUnicode_Check(ptr) == 0 {
+            return Err(ExtractError::WrongType);
+        }
+        if let Some(s) = compact_ascii_str(ptr) {
+            return Ok(s);
+        }
+        let mut size: ffi::Py_ssize_t = 0;
+        let data: *const c_char = ffi::PyUnicode_AsUTF8AndSize(ptr, &mut size);
+        if data.is_null() {
+            // lone surrogates, CPython has already set an exception which we don't want
+            ffi::PyErr_Clear();
+            Err(ExtractError::InvalidUtf8)
+        } else {
+            let bytes = slice::from_raw_parts(data as *const u8, size as usize);
+            Ok(str::from_utf8_unchecked(bytes))
+        }
+    }
+}
+
+// like `PyInt::is_type_of` + `extract::<i64>()`: int subclasses (including bool) are accepted
+pub fn try_extract_i64(obj: &PyAny) -> ExtractResult<i64> {
+    let ptr = obj.as_ptr();
+    unsafe {
+        if ffi::PyLong_Check(ptr) == 0 {
+            return Err(ExtractError::WrongType);
+        }
+        let mut overflow = 0;
+        let value = ffi::PyLong_AsLongLongAndOverflow(ptr, &mut overflow);
+        if overflow != 0 {
+            Err(ExtractError::Overflow)
+        } else {
+            Ok(value)
+        }
+    }
+}
+
+// only float instances, ints are not coerced since `PyLong_AsDouble` can raise `OverflowError`
+pub fn try_extract_f64(obj: &PyAny) -> ExtractResult<f64> {
+    let ptr = obj.as_ptr();
+    unsafe {
+        if ffi::PyFloat_Check(ptr) == 0 {
+            Err(ExtractError::WrongType)
+        } else {
+            Ok(ffi::PyFloat_AsDouble(ptr))
+        }
+    }
+}
+
+pub fn try_extract_bool(obj: &PyAny) -> ExtractResult<bool> {
+    let ptr = obj.as_ptr();
+    unsafe {
+        if ffi::PyBool_Check(ptr) == 0 {
+            Err(ExtractError::WrongType)
+        } else {
+            Ok(ptr == ffi::Py_True())
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    // exact type only: no subclasses, and bool is never accepted as an int
+    Strict,
+    // int-like floats, numeric strings and `__index__` are coerced
+    Lax,
+}
+
+pub fn extract_i64(obj: &PyAny, mode: Mode) -> ExtractResult<i64> {
+    let ptr = obj.as_ptr();
+    unsafe {
+        if ffi::PyLong_CheckExact(ptr) != 0 {
+            return long_as_i64(ptr);
+        }
+    }
+    if mode == Mode::Strict {
+        return Err(ExtractError::WrongType);
+    }
+    unsafe {
+        if ffi::PyLong_Check(ptr) != 0 {
+            long_as_i64(ptr)
+        } else if ffi::PyFloat_Check(ptr) != 0 {
+            float_as_i64(ffi::PyFloat_AsDouble(ptr))
+        } else if ffi::PyUnicode_Check(ptr) != 0 {
+            let s = try_extract_str(obj)?;
+            s.trim().parse().map_err(|_| ExtractError::InvalidValue)
+        } else if ffi::PyIndex_Check(ptr) != 0 {
+            index_as(ptr, long_as_i64)
+        } else {
+            Err(ExtractError::WrongType)
+        }
+    }
+}
+
+pub fn extract_f64(obj: &PyAny, mode: Mode) -> ExtractResult<f64> {
+    let ptr = obj.as_ptr();
+    unsafe {
+        if ffi::PyFloat_CheckExact(ptr) != 0 {
+            return Ok(ffi::PyFloat_AsDouble(ptr));
+        }
+    }
+    if mode == Mode::Strict {
+        return Err(ExtractError::WrongType);
+    }
+    unsafe {
+        if ffi::PyFloat_Check(ptr) != 0 {
+            Ok(ffi::PyFloat_AsDouble(ptr))
+        } else if ffi::PyLong_Check(ptr) != 0 {
+            long_as_f64(ptr)
+        } else if ffi::PyUnicode_Check(ptr) != 0 {
+            let s = try_extract_str(obj)?;
+            s.trim().parse().map_err(|_| ExtractError::InvalidValue)
+        } else if ffi::PyIndex_Check(ptr) != 0 {
+            index_as(ptr, long_as_f64)
+        } else {
+            Err(ExtractError::WrongType)
+        }
+    }
+}
+
+pub fn extract_bool(obj: &PyAny, mode: Mode) -> ExtractResult<bool> {
+    if let Ok(b) = try_extract_bool(obj) {
+        return Ok(b);
+    }
+    if mode == Mode::Strict {
+        return Err(ExtractError::WrongType);
+    }
+    let ptr = obj.as_ptr();
+    unsafe {
+        if ffi::PyLong_Check(ptr) != 0 {
+            match long_as_i64(ptr) {
+                Ok(0) => Ok(false),
+                Ok(1) => Ok(true),
+                _ => Err(ExtractError::InvalidValue),
+            }
+        } else if ffi::PyFloat_Check(ptr) != 0 {
+            match ffi::PyFloat_AsDouble(ptr) {
+                0.0 => Ok(false),
+                1.0 => Ok(true),
+                _ => Err(ExtractError::InvalidValue),
+            }
+        } else if ffi::PyUnicode_Check(ptr) != 0 {
+            let s = try_extract_str(obj)?;
+            str_as_bool(s.trim())
+        } else {
+            Err(ExtractError::WrongType)
+        }
+    }
+}
+
+unsafe fn index_as<T>(ptr: *mut ffi::PyObject, f: unsafe fn(*mut ffi::PyObject) -> ExtractResult<T>) -> ExtractResult<T> {
+    let index = ffi::PyNumber_Index(ptr);
+    if index.is_null() {
+        ffi::PyErr_Clear();
+        return Err(ExtractError::InvalidValue);
+    }
+    let result = f(index);
+    ffi::Py_DECREF(index);
+    result
+}
+
+unsafe fn long_as_i64(ptr: *mut ffi::PyObject) -> ExtractResult<i64> {
+    let mut overflow = 0;
+    let value = ffi::PyLong_AsLongLongAndOverflow(ptr, &mut overflow);
+    if overflow != 0 {
+        Err(ExtractError::Overflow)
+    } else {
+        Ok(value)
+    }
+}
+
+unsafe fn long_as_f64(ptr: *mut ffi::PyObject) -> ExtractResult<f64> {
+    let value = ffi::PyLong_AsDouble(ptr);
+    // -1.0 is also a valid result, so we have to check the error indicator
+    if value == -1.0 && !ffi::PyErr_Occurred().is_null() {
+        ffi::PyErr_Clear();
+        Err(ExtractError::Overflow)
+    } else {
+        Ok(value)
+    }
+}
+
+fn float_as_i64(f: f64) -> ExtractResult<i64> {
+    if f.fract() != 0.0 || f.is_nan() {
+        Err(ExtractError::InvalidValue)
+    } else if !(-9_223_372_036_854_775_808.0..9_223_372_036_854_775_808.0).contains(&f) {
+        // both bounds are exact powers of two, so these comparisons are exact
+        Err(ExtractError::Overflow)
+    } else {
+        Ok(f as i64)
+    }
+}
+
+fn str_as_bool(s: &str) -> ExtractResult<bool> {
+    const TRUE: [&str; 5] = ["1", "true", "t", "yes", "on"];
+    const FALSE: [&str; 5] = ["0", "false", "f", "no", "off"];
+    if TRUE.iter().any(|t| t.eq_ignore_ascii_case(s)) {
+        Ok(true)
+    } else if FALSE.iter().any(|f| f.eq_ignore_ascii_case(s)) {
+        Ok(false)
+    } else {
+        Err(ExtractError::InvalidValue)
+    }
+}
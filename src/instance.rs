@@ -0,0 +1,52 @@
+use std::ptr;
+
+use pyo3::intern;
+use pyo3::prelude::*;
+use pyo3::types::{PyString, PyType};
+use pyo3::{ffi, AsPyPointer};
+
+use crate::PyDictBuilder;
+
+// builds an instance of `class` the way `copy` and `pickle` do: `object.__new__(class)` then the
+// instance `__dict__` is replaced wholesale, so `__init__`, `__setattr__` and any descriptors on the
+// class are all skipped
+pub struct InstanceBuilder {
+    class: Py<PyType>,
+    dict: PyDictBuilder,
+}
+
+impl InstanceBuilder {
+    // `capacity` is the number of attributes, used to pre-size the `__dict__`
+    pub fn with_capacity(py: Python, class: &PyType, capacity: usize) -> PyResult<Self> {
+        Ok(Self {
+            class: class.into(),
+            dict: PyDictBuilder::with_capacity(py, capacity)?,
+        })
+    }
+
+    // the name is interned, as it would be by `setattr`, so attribute lookups on the instance can
+    // compare keys by identity
+    pub fn set_attr(&mut self, py: Python, name: &str, value: impl ToPyObject) -> PyResult<()> {
+        self.dict.set_item(py, PyString::intern(py, name), value)
+    }
+
+    pub fn get(self, py: Python<'_>) -> PyResult<&PyAny> {
+        let instance = object_new(py, self.class.as_ref(py))?;
+        let dict = self.dict.get(py);
+        unsafe {
+            // fails with an AttributeError if instances of the class have no `__dict__`, e.g. `__slots__`
+            match ffi::PyObject_GenericSetDict(instance.as_ptr(), dict.as_ptr(), ptr::null_mut()) {
+                0 => Ok(instance),
+                _ => Err(PyErr::fetch(py)),
+            }
+        }
+    }
+}
+
+// called through Python rather than via the `tp_new` slot, which would skip the check that
+// `object.__new__` is safe for `class`, e.g. that it isn't a subclass of `int`
+fn object_new<'py>(py: Python<'py>, class: &PyType) -> PyResult<&'py PyAny> {
+    let object: &PyType =
+        unsafe { py.from_borrowed_ptr(ptr::addr_of_mut!(ffi::PyBaseObject_Type) as *mut ffi::PyObject) };
+    object.call_method1(intern!(py, "__new__"), (class,))
+}
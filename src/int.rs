@@ -0,0 +1,102 @@
+use pyo3::prelude::*;
+use pyo3::types::PyInt;
+use pyo3::{ffi, AsPyPointer};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IntValue {
+    Int(i64),
+    Big(BigInt),
+}
+
+// arbitrary precision int stored as little-endian two's complement bytes, the same layout as
+// `int.to_bytes(n, 'little', signed=True)`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BigInt {
+    bytes: Vec<u8>,
+}
+
+impl BigInt {
+    pub fn to_le_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.bytes.last().is_some_and(|b| b & 0x80 != 0)
+    }
+
+    pub fn to_i128(&self) -> Option<i128> {
+        if self.bytes.len() > 16 {
+            return None;
+        }
+        let fill = if self.is_negative() { 0xff } else { 0 };
+        let mut buf = [fill; 16];
+        buf[..self.bytes.len()].copy_from_slice(&self.bytes);
+        Some(i128::from_le_bytes(buf))
+    }
+}
+
+impl ToPyObject for BigInt {
+    #[cfg(not(Py_LIMITED_API))]
+    fn to_object(&self, py: Python) -> PyObject {
+        unsafe {
+            let ptr = ffi::_PyLong_FromByteArray(self.bytes.as_ptr(), self.bytes.len(), 1, 1);
+            PyObject::from_owned_ptr(py, ptr)
+        }
+    }
+
+    #[cfg(Py_LIMITED_API)]
+    fn to_object(&self, py: Python) -> PyObject {
+        use pyo3::types::{PyBytes, PyDict};
+
+        let kwargs = PyDict::new(py);
+        kwargs.set_item("signed", true).unwrap();
+        py.get_type::<PyInt>()
+            .call_method("from_bytes", (PyBytes::new(py, &self.bytes), "little"), Some(kwargs))
+            .unwrap()
+            .into()
+    }
+}
+
+pub fn extract_int(int: &PyInt) -> PyResult<IntValue> {
+    let ptr = int.as_ptr();
+    let mut overflow = 0;
+    let value = unsafe { ffi::PyLong_AsLongLongAndOverflow(ptr, &mut overflow) };
+    if overflow == 0 {
+        if value == -1 {
+            if let Some(err) = PyErr::take(int.py()) {
+                return Err(err);
+            }
+        }
+        Ok(IntValue::Int(value))
+    } else {
+        extract_big_int(int).map(IntValue::Big)
+    }
+}
+
+#[cfg(not(Py_LIMITED_API))]
+fn extract_big_int(int: &PyInt) -> PyResult<BigInt> {
+    let ptr = int.as_ptr();
+    unsafe {
+        // one extra byte for the sign bit
+        let n_bytes = ffi::_PyLong_NumBits(ptr) / 8 + 1;
+        let mut bytes = vec![0u8; n_bytes];
+        match ffi::_PyLong_AsByteArray(ptr as *mut ffi::PyLongObject, bytes.as_mut_ptr(), n_bytes, 1, 1) {
+            0 => Ok(BigInt { bytes }),
+            _ => Err(PyErr::fetch(int.py())),
+        }
+    }
+}
+
+#[cfg(Py_LIMITED_API)]
+fn extract_big_int(int: &PyInt) -> PyResult<BigInt> {
+    use pyo3::types::PyDict;
+
+    let py = int.py();
+    let n_bits: usize = int.call_method0("bit_length")?.extract()?;
+    let kwargs = PyDict::new(py);
+    kwargs.set_item("signed", true)?;
+    let bytes: Vec<u8> = int
+        .call_method("to_bytes", (n_bits / 8 + 1, "little"), Some(kwargs))?
+        .extract()?;
+    Ok(BigInt { bytes })
+}
@@ -0,0 +1,176 @@
+use std::iter::FusedIterator;
+
+use pyo3::exceptions::PyRuntimeError;
+use pyo3::prelude::*;
+use pyo3::types::{PyIterator, PyList, PyTuple};
+use pyo3::{ffi, AsPyPointer};
+
+use crate::number_list::{list_get_item, tuple_get_item};
+
+pub enum FastIter<'py> {
+    List { list: &'py PyList, index: usize },
+    Tuple { tuple: &'py PyTuple, index: usize },
+    Iter(&'py PyIterator),
+}
+
+pub fn fast_iter(obj: &PyAny) -> PyResult<FastIter<'_>> {
+    let py = obj.py();
+    let ptr = obj.as_ptr();
+    unsafe {
+        if ffi::PyList_CheckExact(ptr) != 0 {
+            Ok(FastIter::List { list: obj.downcast_unchecked(), index: 0 })
+        } else if ffi::PyTuple_CheckExact(ptr) != 0 {
+            Ok(FastIter::Tuple { tuple: obj.downcast_unchecked(), index: 0 })
+        } else if ffi::PyList_Check(ptr) != 0 || ffi::PyTuple_Check(ptr) != 0 {
+            // subclasses can override `__iter__` or `__getitem__`, which indexing the items directly
+            // would skip, so they're iterated like any other object
+            Ok(FastIter::Iter(obj.iter()?))
+        } else if ffi::PySequence_Check(ptr) != 0 {
+            // any other sequence is iterated once and copied into a new list
+            let seq_ptr = ffi::PySequence_Fast(ptr, c"expected a sequence".as_ptr());
+            let seq: &PyAny = py.from_owned_ptr_or_err(seq_ptr)?;
+            if ffi::PyList_Check(seq.as_ptr()) != 0 {
+                Ok(FastIter::List { list: seq.downcast_unchecked(), index: 0 })
+            } else {
+                Ok(FastIter::Tuple { tuple: seq.downcast_unchecked(), index: 0 })
+            }
+        } else {
+            Ok(FastIter::Iter(obj.iter()?))
+        }
+    }
+}
+
+impl<'py> Iterator for FastIter<'py> {
+    type Item = PyResult<&'py PyAny>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            // the length is checked every time in case the list is mutated while we iterate
+            FastIter::List { list, index } => {
+                if *index >= list.len() {
+                    return None;
+                }
+                let item = unsafe { owned_item(list.py(), list_get_item(list, *index)) };
+                *index += 1;
+                Some(Ok(item))
+            }
+            FastIter::Tuple { tuple, index } => {
+                if *index >= tuple.len() {
+                    return None;
+                }
+                let item = unsafe { owned_item(tuple.py(), tuple_get_item(tuple, *index)) };
+                *index += 1;
+                Some(Ok(item))
+            }
+            FastIter::Iter(iter) => iter.next(),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self {
+            FastIter::List { list, index } => (list.len().saturating_sub(*index), None),
+            FastIter::Tuple { tuple, index } => {
+                let remaining = tuple.len() - *index;
+                (remaining, Some(remaining))
+            }
+            FastIter::Iter(_) => (0, None),
+        }
+    }
+}
+
+// the item must outlive the container slot it came from, in case the list is mutated later
+unsafe fn owned_item<'py>(py: Python<'py>, ptr: *mut ffi::PyObject) -> &'py PyAny {
+    ffi::Py_INCREF(ptr);
+    py.from_owned_ptr(ptr)
+}
+
+// yields items without taking a new reference to each of them, the items are only kept alive by
+// the container
+pub enum BorrowedIter<'a> {
+    List {
+        list: &'a PyList,
+        index: usize,
+        len: usize,
+        #[cfg(not(Py_LIMITED_API))]
+        ob_item: *mut *mut ffi::PyObject,
+    },
+    Tuple { tuple: &'a PyTuple, index: usize },
+}
+
+impl<'a> BorrowedIter<'a> {
+    /// # Safety
+    ///
+    /// The list mustn't be mutated while the iterator or any item it yielded is alive. Changes to
+    /// the list's length or a reallocation of its storage are detected and reported as an error,
+    /// but replacing an item in place (e.g. `list[0] = x`) can't be detected.
+    pub unsafe fn list(list: &'a PyList) -> Self {
+        Self::List {
+            list,
+            index: 0,
+            len: list.len(),
+            #[cfg(not(Py_LIMITED_API))]
+            ob_item: (*(list.as_ptr() as *mut ffi::PyListObject)).ob_item,
+        }
+    }
+
+    // tuples are immutable, so this is always safe
+    pub fn tuple(tuple: &'a PyTuple) -> Self {
+        Self::Tuple { tuple, index: 0 }
+    }
+}
+
+impl<'a> Iterator for BorrowedIter<'a> {
+    type Item = PyResult<&'a PyAny>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            #[cfg(not(Py_LIMITED_API))]
+            BorrowedIter::List { list, index, len, ob_item } => {
+                if *index >= *len {
+                    return None;
+                }
+                let current_ob_item = unsafe { (*(list.as_ptr() as *mut ffi::PyListObject)).ob_item };
+                if list.len() != *len || current_ob_item != *ob_item {
+                    return Some(Err(mutated_error(index, *len)));
+                }
+                borrowed_next(list.py(), index, *len, |i| unsafe { list_get_item(list, i) })
+            }
+            #[cfg(Py_LIMITED_API)]
+            BorrowedIter::List { list, index, len } => {
+                if *index >= *len {
+                    return None;
+                }
+                if list.len() != *len {
+                    return Some(Err(mutated_error(index, *len)));
+                }
+                borrowed_next(list.py(), index, *len, |i| unsafe { list_get_item(list, i) })
+            }
+            BorrowedIter::Tuple { tuple, index } => {
+                borrowed_next(tuple.py(), index, tuple.len(), |i| unsafe { tuple_get_item(tuple, i) })
+            }
+        }
+    }
+}
+
+impl FusedIterator for BorrowedIter<'_> {}
+
+// the iterator is exhausted after the error so that loops which skip errors still end
+fn mutated_error(index: &mut usize, len: usize) -> PyErr {
+    *index = len;
+    PyRuntimeError::new_err("list mutated during iteration")
+}
+
+fn borrowed_next<'a>(
+    py: Python<'a>,
+    index: &mut usize,
+    len: usize,
+    get_item: impl Fn(usize) -> *mut ffi::PyObject,
+) -> Option<PyResult<&'a PyAny>> {
+    if *index >= len {
+        return None;
+    }
+    // `from_borrowed_ptr` doesn't incref, it just casts the pointer
+    let item = unsafe { py.from_borrowed_ptr(get_item(*index)) };
+    *index += 1;
+    Some(Ok(item))
+}
@@ -0,0 +1,60 @@
+use std::ptr::addr_of_mut;
+
+use pyo3::prelude::*;
+use pyo3::{ffi, AsPyPointer};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PyKind {
+    Str,
+    Int,
+    Float,
+    Bool,
+    None,
+    List,
+    Dict,
+    Other,
+}
+
+pub fn classify(obj: &PyAny) -> PyKind {
+    let ptr = obj.as_ptr();
+    unsafe {
+        if ptr == ffi::Py_None() {
+            return PyKind::None;
+        }
+        // exact types first, these are just pointer comparisons
+        let ob_type = ffi::Py_TYPE(ptr);
+        if ob_type == addr_of_mut!(ffi::PyUnicode_Type) {
+            PyKind::Str
+        // bool must come before int since bool is a subclass of int
+        } else if ob_type == addr_of_mut!(ffi::PyBool_Type) {
+            PyKind::Bool
+        } else if ob_type == addr_of_mut!(ffi::PyLong_Type) {
+            PyKind::Int
+        } else if ob_type == addr_of_mut!(ffi::PyFloat_Type) {
+            PyKind::Float
+        } else if ob_type == addr_of_mut!(ffi::PyList_Type) {
+            PyKind::List
+        } else if ob_type == addr_of_mut!(ffi::PyDict_Type) {
+            PyKind::Dict
+        } else {
+            classify_subclass(ptr)
+        }
+    }
+}
+
+// bool can't be subclassed, so it's covered by the exact check above
+unsafe fn classify_subclass(ptr: *mut ffi::PyObject) -> PyKind {
+    if ffi::PyUnicode_Check(ptr) != 0 {
+        PyKind::Str
+    } else if ffi::PyLong_Check(ptr) != 0 {
+        PyKind::Int
+    } else if ffi::PyFloat_Check(ptr) != 0 {
+        PyKind::Float
+    } else if ffi::PyList_Check(ptr) != 0 {
+        PyKind::List
+    } else if ffi::PyDict_Check(ptr) != 0 {
+        PyKind::Dict
+    } else {
+        PyKind::Other
+    }
+}
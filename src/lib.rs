@@ -1,11 +1,107 @@
 use std::cmp::Ordering;
 
-use pyo3::{AsPyPointer};
+use pyo3::{create_exception, AsPyPointer};
 use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
 use pyo3::ffi;
-use pyo3::types::{PyList, PyTuple};
+use pyo3::types::{PyDict, PyList, PyTuple};
 
+mod buffer;
+mod extract;
+mod instance;
+mod int;
+mod iter;
+mod kind;
+mod number_cache;
+mod number_list;
+mod parallel;
+mod prefix;
+mod record;
+mod row_set;
+mod string;
+mod string_cache;
+mod text_builder;
+mod typed_list;
+mod value;
+
+pub use buffer::BufferSlice;
+pub use extract::{
+    extract_bool, extract_f64, extract_i64, try_extract_bool, try_extract_f64, try_extract_i64, try_extract_str,
+    ExtractError, ExtractResult, Mode,
+};
+pub use instance::InstanceBuilder;
+pub use int::{extract_int, BigInt, IntValue};
+pub use iter::{fast_iter, BorrowedIter, FastIter};
+pub use kind::{classify, PyKind};
+pub use number_cache::NumberCache;
+pub use number_list::{extract_f64_list, extract_i64_list};
+pub use parallel::{par_map, par_map_i64_list, par_map_str_list};
+pub use prefix::{count_prefix, count_suffix, filter_prefix, filter_suffix};
+pub use record::{RecordBuilder, RecordKind, RecordType};
+pub use row_set::{ColumnType, RowSetBuilder};
+pub use string::{
+    borrow_str, extract_str_arena, extract_str_list, new_ascii_string, new_latin1_string, StrArena, StrPath, Validation,
+};
+pub use string_cache::{CacheStats, StringCache};
+pub use text_builder::{PyBytesBuilder, PyStringBuilder};
+pub use typed_list::{TypedItem, TypedListBuilder};
+pub use value::{from_value, to_value, to_value_max_depth, FromValueOptions, Value, DEFAULT_MAX_DEPTH};
+
+
+// `PyErr::set_cause` drops the cause's traceback (pyo3 0.18 doesn't copy it onto the exception
+// value), so it's attached to the value here first
+fn set_cause(py: Python, err: &PyErr, cause: PyErr) {
+    if let Some(traceback) = cause.traceback(py) {
+        unsafe { ffi::PyException_SetTraceback(cause.value(py).as_ptr(), traceback.as_ptr()) };
+    }
+    err.set_cause(py, Some(cause));
+}
+
+// raised by `try_fill` when producing an item fails, the original error is its `__cause__`
+create_exception!(rust_bench, FillError, PyValueError);
+
+fn check_fill(len: usize, remaining: usize) -> PyResult<()> {
+    match len <= remaining {
+        true => Ok(()),
+        false => Err(PyValueError::new_err(format!(
+            "try_fill() of {} items exceeds remaining capacity {}",
+            len, remaining
+        ))),
+    }
+}
+
+fn fill_error(py: Python, index: usize, err: PyErr) -> PyErr {
+    let fill_err = FillError::new_err(format!("item {}: {}", index, err));
+    set_cause(py, &fill_err, err);
+    fill_err
+}
+
+// the number of items pushed when it was taken, see `rollback_to`. The address of the builder's
+// list or tuple is kept too so a checkpoint from another live builder is rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Checkpoint {
+    owner: usize,
+    counter: ffi::Py_ssize_t,
+}
+
+impl Checkpoint {
+    fn new(owner: *mut ffi::PyObject, counter: ffi::Py_ssize_t) -> Self {
+        Self {
+            owner: owner as usize,
+            counter,
+        }
+    }
+}
+
+fn check_rollback(checkpoint: Checkpoint, owner: *mut ffi::PyObject, counter: ffi::Py_ssize_t) -> PyResult<()> {
+    if checkpoint.owner != owner as usize {
+        Err(PyValueError::new_err("checkpoint is from a different builder"))
+    } else if checkpoint.counter > counter {
+        Err(PyValueError::new_err("checkpoint is ahead of the builder"))
+    } else {
+        Ok(())
+    }
+}
 
 pub struct PyListBuilder {
     len: ffi::Py_ssize_t,
@@ -39,23 +135,85 @@ impl PyListBuilder {
     }
 
     pub fn push_alt(&mut self, py: Python, item: impl ToPyObject) -> PyResult<()> {
+        unsafe { self.push_owned_ptr(item.to_object(py).into_ptr()) }
+    }
+
+    // `obj_ptr` must be a new reference, it's consumed either way: stored in the list, or released
+    // if the list is already full
+    pub(crate) unsafe fn push_owned_ptr(&mut self, obj_ptr: *mut ffi::PyObject) -> PyResult<()> {
         if self.counter == self.len {
-            Err(PyValueError::new_err("push() exceeded list capacity"))
-        } else {
-            unsafe {
-                let obj_ptr = item.to_object(py).into_ptr();
+            ffi::Py_DECREF(obj_ptr);
+            return Err(PyValueError::new_err("push() exceeded list capacity"));
+        }
+        #[cfg(not(Py_LIMITED_API))]
+        ffi::PyList_SET_ITEM(self.list.as_ptr(), self.counter, obj_ptr);
+        #[cfg(Py_LIMITED_API)]
+        ffi::PyList_SetItem(self.list.as_ptr(), self.counter, obj_ptr);
+        self.counter += 1;
+        Ok(())
+    }
 
+    // pushes `f(0)` to `f(len - 1)`, if any call fails the items pushed so far are rolled back and
+    // the error is wrapped in a `FillError` naming the failing index
+    pub fn try_fill<T: ToPyObject>(
+        &mut self,
+        py: Python,
+        len: usize,
+        mut f: impl FnMut(usize) -> PyResult<T>,
+    ) -> PyResult<()> {
+        check_fill(len, self.remaining())?;
+        let checkpoint = self.checkpoint();
+        for index in 0..len {
+            match f(index) {
+                Ok(item) => self.push(py, item)?,
+                Err(err) => {
+                    self.rollback_to(checkpoint)?;
+                    return Err(fill_error(py, index, err));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // the number of items pushed so far
+    pub fn len(&self) -> usize {
+        self.counter as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.counter == 0
+    }
+
+    pub fn remaining(&self) -> usize {
+        (self.len - self.counter) as usize
+    }
+
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint::new(self.list.as_ptr(), self.counter)
+    }
+
+    // drops every item pushed since `checkpoint`, those slots can then be pushed to again
+    pub fn rollback_to(&mut self, checkpoint: Checkpoint) -> PyResult<()> {
+        let ptr = self.list.as_ptr();
+        check_rollback(checkpoint, ptr, self.counter)?;
+        for index in checkpoint.counter..self.counter {
+            unsafe {
                 #[cfg(not(Py_LIMITED_API))]
-                ffi::PyList_SET_ITEM(self.list.as_ptr(), self.counter, obj_ptr);
+                {
+                    let item = ffi::PyList_GET_ITEM(ptr, index);
+                    ffi::PyList_SET_ITEM(ptr, index, std::ptr::null_mut());
+                    ffi::Py_XDECREF(item);
+                }
+                // decrefs the old item
                 #[cfg(Py_LIMITED_API)]
-                ffi::PyList_SetItem(self.list.as_ptr(), self.counter, obj_ptr);
+                ffi::PyList_SetItem(ptr, index, std::ptr::null_mut());
             }
-            self.counter += 1;
-            Ok(())
         }
+        self.counter = checkpoint.counter;
+        Ok(())
     }
 
-    pub fn get(self, py: Python) -> PyResult<&PyList> {
+    pub fn get(self, py: Python<'_>) -> PyResult<&PyList> {
         match self.counter.cmp(&self.len) {
             // we've filled the list, return it
             Ordering::Equal => Ok(self.list.into_ref(py)),
@@ -66,7 +224,7 @@ impl PyListBuilder {
         }
     }
 
-    pub fn get_incomplete(self, py: Python) -> &PyList {
+    pub fn get_incomplete(self, py: Python<'_>) -> &PyList {
         match self.counter.cmp(&self.len) {
             // we've filled the list, return it
             Ordering::Equal => self.list.into_ref(py),
@@ -81,6 +239,20 @@ impl PyListBuilder {
             Ordering::Greater => unreachable!("complete() exceeded list capacity"),
         }
     }
+
+    // like `get_incomplete` but deletes the unfilled slots in place rather than copying the items
+    // into a new list
+    pub fn get_truncated(self, py: Python<'_>) -> PyResult<&PyList> {
+        if self.counter < self.len {
+            unsafe {
+                let ptr = self.list.as_ptr();
+                if ffi::PyList_SetSlice(ptr, self.counter, self.len, std::ptr::null_mut()) != 0 {
+                    return Err(PyErr::fetch(py));
+                }
+            }
+        }
+        Ok(self.list.into_ref(py))
+    }
 }
 
 pub fn list_as_tuple<'py>(py: Python<'py>, list: &'py PyList) -> &'py PyTuple {
@@ -122,7 +294,88 @@ impl PyTupleBuilder {
             }
         }
     }
-    pub fn get(self, py: Python) -> PyResult<&PyTuple> {
+
+    // pushes `f(0)` to `f(len - 1)`, if any call fails the items pushed so far are rolled back and
+    // the error is wrapped in a `FillError` naming the failing index
+    pub fn try_fill<T: ToPyObject>(
+        &mut self,
+        py: Python,
+        len: usize,
+        mut f: impl FnMut(usize) -> PyResult<T>,
+    ) -> PyResult<()> {
+        check_fill(len, self.remaining())?;
+        let checkpoint = self.checkpoint();
+        for index in 0..len {
+            match f(index) {
+                Ok(item) => self.push(py, item)?,
+                Err(err) => {
+                    self.rollback_to(checkpoint)?;
+                    return Err(fill_error(py, index, err));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // the number of items pushed so far
+    pub fn len(&self) -> usize {
+        self.counter as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.counter == 0
+    }
+
+    pub fn remaining(&self) -> usize {
+        (self.len - self.counter) as usize
+    }
+
+    // hands over the tuple whether or not it's full, the caller must fill it before Python sees it
+    pub(crate) fn into_ptr(self) -> *mut ffi::PyObject {
+        self.tuple.into_ptr()
+    }
+
+    // `obj_ptr` must be a new reference, it's consumed either way: stored in the tuple, or released
+    // if the tuple is already full
+    pub(crate) unsafe fn push_owned_ptr(&mut self, obj_ptr: *mut ffi::PyObject) -> PyResult<()> {
+        if self.counter == self.len {
+            ffi::Py_DECREF(obj_ptr);
+            return Err(PyValueError::new_err("push() exceeded tuple capacity"));
+        }
+        #[cfg(not(Py_LIMITED_API))]
+        ffi::PyTuple_SET_ITEM(self.tuple.as_ptr(), self.counter, obj_ptr);
+        #[cfg(Py_LIMITED_API)]
+        ffi::PyTuple_SetItem(self.tuple.as_ptr(), self.counter, obj_ptr);
+        self.counter += 1;
+        Ok(())
+    }
+
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint::new(self.tuple.as_ptr(), self.counter)
+    }
+
+    // drops every item pushed since `checkpoint`, those slots can then be pushed to again
+    pub fn rollback_to(&mut self, checkpoint: Checkpoint) -> PyResult<()> {
+        let ptr = self.tuple.as_ptr();
+        check_rollback(checkpoint, ptr, self.counter)?;
+        for index in checkpoint.counter..self.counter {
+            unsafe {
+                #[cfg(not(Py_LIMITED_API))]
+                {
+                    let item = ffi::PyTuple_GET_ITEM(ptr, index);
+                    ffi::PyTuple_SET_ITEM(ptr, index, std::ptr::null_mut());
+                    ffi::Py_XDECREF(item);
+                }
+                // decrefs the old item, allowed because nothing else holds a reference to the tuple yet
+                #[cfg(Py_LIMITED_API)]
+                ffi::PyTuple_SetItem(ptr, index, std::ptr::null_mut());
+            }
+        }
+        self.counter = checkpoint.counter;
+        Ok(())
+    }
+
+    pub fn get(self, py: Python<'_>) -> PyResult<&PyTuple> {
         match self.counter.cmp(&self.len) {
             // we've filled the tuple, return it
             Ordering::Equal => Ok(self.tuple.into_ref(py)),
@@ -133,7 +386,7 @@ impl PyTupleBuilder {
         }
     }
 
-    pub fn get_incomplete(self, py: Python) -> &PyTuple {
+    pub fn get_incomplete(self, py: Python<'_>) -> &PyTuple {
         match self.counter.cmp(&self.len) {
             // we've filled the tuple, return it
             Ordering::Equal => self.tuple.into_ref(py),
@@ -148,4 +401,75 @@ impl PyTupleBuilder {
             Ordering::Greater => unreachable!("complete() exceeded tuple capacity"),
         }
     }
+
+    // like `get_incomplete` but shrinks the tuple in place rather than copying the items into a new
+    // tuple, falling back to a slice if the tuple isn't uniquely referenced
+    #[cfg(not(Py_LIMITED_API))]
+    pub fn get_truncated(mut self, py: Python<'_>) -> PyResult<&PyTuple> {
+        if self.counter < self.len && unsafe { ffi::Py_REFCNT(self.tuple.as_ptr()) } == 1 {
+            unsafe {
+                let mut ptr = self.tuple.as_ptr();
+                if _PyTuple_Resize(&mut ptr, self.counter) == 0 {
+                    // our reference moved into `ptr` (possibly reallocated), so overwrite without a decref
+                    std::ptr::write(&mut self.tuple, Py::from_owned_ptr(py, ptr));
+                } else {
+                    // the old tuple has already been freed, leave an empty one in its place so drop is sound
+                    std::ptr::write(&mut self.tuple, PyTuple::empty(py).into());
+                    return Err(PyErr::fetch(py));
+                }
+            }
+            return Ok(self.tuple.into_ref(py));
+        }
+        Ok(self.get_incomplete(py))
+    }
+
+    #[cfg(Py_LIMITED_API)]
+    pub fn get_truncated(self, py: Python<'_>) -> PyResult<&PyTuple> {
+        Ok(self.get_incomplete(py))
+    }
+}
+
+// not exposed by pyo3-ffi, the unfilled (null) slots past `newsize` are cleared with `Py_XDECREF`
+#[cfg(not(Py_LIMITED_API))]
+extern "C" {
+    fn _PyTuple_Resize(p: *mut *mut ffi::PyObject, newsize: ffi::Py_ssize_t) -> std::os::raw::c_int;
+}
+
+pub struct PyDictBuilder {
+    dict: Py<PyDict>,
+}
+
+impl PyDictBuilder {
+    pub fn with_capacity(py: Python, capacity: usize) -> PyResult<Self> {
+        let len: ffi::Py_ssize_t = capacity
+            .try_into()
+            .map_err(|_| PyValueError::new_err("dict len out of range"))?;
+        unsafe {
+            #[cfg(not(Py_LIMITED_API))]
+            let ptr = ffi::_PyDict_NewPresized(len);
+            #[cfg(Py_LIMITED_API)]
+            let ptr = {
+                let _ = len;
+                ffi::PyDict_New()
+            };
+            let dict: Py<PyDict> = Py::from_owned_ptr_or_err(py, ptr)?;
+            Ok(Self { dict })
+        }
+    }
+
+    pub fn set_item(&mut self, py: Python, key: impl ToPyObject, value: impl ToPyObject) -> PyResult<()> {
+        let key = key.to_object(py);
+        let value = value.to_object(py);
+        unsafe {
+            match ffi::PyDict_SetItem(self.dict.as_ptr(), key.as_ptr(), value.as_ptr()) {
+                0 => Ok(()),
+                // e.g. an unhashable key
+                _ => Err(PyErr::fetch(py)),
+            }
+        }
+    }
+
+    pub fn get(self, py: Python<'_>) -> &PyDict {
+        self.dict.into_ref(py)
+    }
 }
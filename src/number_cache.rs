@@ -0,0 +1,86 @@
+use pyo3::prelude::*;
+
+use crate::string_cache::CacheStats;
+
+// CPython keeps singletons for ints in this range, see `_PY_NSMALLNEGINTS` and `_PY_NSMALLPOSINTS`
+const SMALL_INT_MIN: i64 = -5;
+const SMALL_INT_MAX: i64 = 256;
+
+// like `StringCache`, but keyed by value (or bit pattern for floats) so there's no key to store
+// beyond the number itself
+pub struct NumberCache {
+    small_ints: Vec<PyObject>,
+    ints: Vec<Option<(i64, PyObject)>>,
+    floats: Vec<Option<(u64, PyObject)>>,
+    shift: u32,
+    stats: CacheStats,
+}
+
+impl NumberCache {
+    // `capacity` is rounded up to a power of two, and applies separately to ints and floats
+    pub fn with_capacity(py: Python, capacity: usize) -> Self {
+        let capacity = capacity.max(2).next_power_of_two();
+        Self {
+            small_ints: (SMALL_INT_MIN..=SMALL_INT_MAX).map(|i| i.to_object(py)).collect(),
+            ints: (0..capacity).map(|_| None).collect(),
+            floats: (0..capacity).map(|_| None).collect(),
+            shift: 64 - capacity.trailing_zeros(),
+            stats: CacheStats::default(),
+        }
+    }
+
+    pub fn int(&mut self, py: Python, value: i64) -> PyObject {
+        // not counted in the stats, these never touch the cache so would only inflate the hit rate
+        if (SMALL_INT_MIN..=SMALL_INT_MAX).contains(&value) {
+            return self.small_ints[(value - SMALL_INT_MIN) as usize].clone_ref(py);
+        }
+        let index = self.index(value as u64);
+        cached(py, &mut self.ints, index, &mut self.stats, value, || value.to_object(py))
+    }
+
+    pub fn float(&mut self, py: Python, value: f64) -> PyObject {
+        let bits = value.to_bits();
+        let index = self.index(bits);
+        cached(py, &mut self.floats, index, &mut self.stats, bits, || value.to_object(py))
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        self.stats
+    }
+
+    // fibonacci hashing, spreads both sequential ints and round floats (whose low bits are all
+    // zero) across the slots
+    fn index(&self, key: u64) -> usize {
+        (key.wrapping_mul(0x9E37_79B9_7F4A_7C15) >> self.shift) as usize
+    }
+}
+
+fn cached<K: PartialEq>(
+    py: Python,
+    slots: &mut [Option<(K, PyObject)>],
+    index: usize,
+    stats: &mut CacheStats,
+    key: K,
+    create: impl FnOnce() -> PyObject,
+) -> PyObject {
+    let pair = index & !1;
+    for (slot_key, value) in slots[pair..pair + 2].iter().flatten() {
+        if *slot_key == key {
+            stats.hits += 1;
+            return value.clone_ref(py);
+        }
+    }
+    stats.misses += 1;
+    // fill the empty slot of the pair if there is one, otherwise replace the one the key hashed to
+    let target = match (&slots[index], &slots[index ^ 1]) {
+        (Some(_), None) => index ^ 1,
+        (None, _) => index,
+        (Some(_), Some(_)) => {
+            stats.evictions += 1;
+            index
+        }
+    };
+    let value = create();
+    slots[target] = Some((key, value.clone_ref(py)));
+    value
+}
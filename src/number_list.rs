@@ -0,0 +1,115 @@
+use pyo3::exceptions::{PyException, PyMemoryError, PyOverflowError, PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyList, PyTuple};
+use pyo3::{ffi, AsPyPointer};
+
+use crate::set_cause;
+
+pub fn extract_i64_list(seq: &PyAny) -> PyResult<Vec<i64>> {
+    extract_number_list(seq, |ptr| unsafe {
+        if ffi::PyLong_CheckExact(ptr) != 0 {
+            let mut overflow = 0;
+            let value = ffi::PyLong_AsLongLongAndOverflow(ptr, &mut overflow);
+            if overflow == 0 {
+                return Some(value);
+            }
+        }
+        // let pyo3 deal with subclasses, `__index__` and building the overflow error
+        None
+    })
+}
+
+pub fn extract_f64_list(seq: &PyAny) -> PyResult<Vec<f64>> {
+    extract_number_list(seq, |ptr| unsafe {
+        if ffi::PyFloat_CheckExact(ptr) != 0 {
+            Some(ffi::PyFloat_AsDouble(ptr))
+        } else {
+            None
+        }
+    })
+}
+
+// `fast` mustn't run any Python code, it's called with a borrowed reference to the item
+fn extract_number_list<T>(seq: &PyAny, fast: fn(*mut ffi::PyObject) -> Option<T>) -> PyResult<Vec<T>>
+where
+    T: for<'a> FromPyObject<'a>,
+{
+    let py = seq.py();
+    if let Ok(list) = seq.downcast::<PyList>() {
+        let mut values = Vec::with_capacity(list.len());
+        let mut index = 0;
+        // the length is checked every time since the fallback can run Python code which might
+        // mutate the list
+        while index < list.len() {
+            let item_ptr = unsafe { list_get_item(list, index) };
+            values.push(extract_item(py, item_ptr, index, fast)?);
+            index += 1;
+        }
+        Ok(values)
+    } else if let Ok(tuple) = seq.downcast::<PyTuple>() {
+        let mut values = Vec::with_capacity(tuple.len());
+        for index in 0..tuple.len() {
+            let item_ptr = unsafe { tuple_get_item(tuple, index) };
+            values.push(extract_item(py, item_ptr, index, fast)?);
+        }
+        Ok(values)
+    } else {
+        let type_name = seq.get_type().name()?;
+        Err(PyTypeError::new_err(format!("expected list or tuple, got {}", type_name)))
+    }
+}
+
+fn extract_item<T>(
+    py: Python,
+    item_ptr: *mut ffi::PyObject,
+    index: usize,
+    fast: fn(*mut ffi::PyObject) -> Option<T>,
+) -> PyResult<T>
+where
+    T: for<'a> FromPyObject<'a>,
+{
+    match fast(item_ptr) {
+        Some(value) => Ok(value),
+        None => {
+            // take a new reference before handing the item to code which may run Python
+            let item: &PyAny = unsafe {
+                ffi::Py_INCREF(item_ptr);
+                py.from_owned_ptr(item_ptr)
+            };
+            item.extract().map_err(|err| index_error(py, index, err))
+        }
+    }
+}
+
+pub(crate) unsafe fn list_get_item(list: &PyList, index: usize) -> *mut ffi::PyObject {
+    #[cfg(not(Py_LIMITED_API))]
+    return ffi::PyList_GET_ITEM(list.as_ptr(), index as ffi::Py_ssize_t);
+    #[cfg(Py_LIMITED_API)]
+    return ffi::PyList_GetItem(list.as_ptr(), index as ffi::Py_ssize_t);
+}
+
+pub(crate) unsafe fn tuple_get_item(tuple: &PyTuple, index: usize) -> *mut ffi::PyObject {
+    #[cfg(not(Py_LIMITED_API))]
+    return ffi::PyTuple_GET_ITEM(tuple.as_ptr(), index as ffi::Py_ssize_t);
+    #[cfg(Py_LIMITED_API)]
+    return ffi::PyTuple_GetItem(tuple.as_ptr(), index as ffi::Py_ssize_t);
+}
+
+// rebuilding `err` as its own type would call that type's constructor, which may need other
+// arguments, so it's wrapped in a fixed type instead and kept as the `__cause__`. Anything that isn't
+// a problem with the item, e.g. `KeyboardInterrupt` or `MemoryError`, is passed through as it is.
+fn index_error(py: Python, index: usize, err: PyErr) -> PyErr {
+    if !err.is_instance_of::<PyException>(py) || err.is_instance_of::<PyMemoryError>(py) {
+        return err;
+    }
+    let msg = format!("item {}: {}", index, err.value(py));
+    let wrapped = if err.is_instance_of::<PyTypeError>(py) {
+        PyTypeError::new_err(msg)
+    } else if err.is_instance_of::<PyOverflowError>(py) {
+        PyOverflowError::new_err(msg)
+    } else {
+        PyValueError::new_err(msg)
+    };
+    set_cause(py, &wrapped, err);
+    wrapped
+}
@@ -0,0 +1,68 @@
+use std::thread;
+
+use pyo3::prelude::*;
+use pyo3::types::PyList;
+
+use crate::number_list::extract_i64_list;
+use crate::string::extract_str_list;
+use crate::PyListBuilder;
+
+pub fn par_map_str_list<R, F>(list: &PyList, n_threads: usize, f: F) -> PyResult<&PyList>
+where
+    R: ToPyObject + Send,
+    F: Fn(&str) -> R + Sync,
+{
+    // the strs point into Python objects which are kept alive (and are immutable) while the GIL
+    // is released, so there's no need to copy them
+    let strs = extract_str_list(list)?;
+    par_map(list.py(), &strs, n_threads, |s| f(s))
+}
+
+pub fn par_map_i64_list<R, F>(list: &PyList, n_threads: usize, f: F) -> PyResult<&PyList>
+where
+    R: ToPyObject + Send,
+    F: Fn(i64) -> R + Sync,
+{
+    let ints = extract_i64_list(list)?;
+    par_map(list.py(), &ints, n_threads, |i| f(*i))
+}
+
+// runs `f` over `items` in `n_threads` chunks with the GIL released, then builds a list of the
+// results in the original order
+pub fn par_map<'py, T, R, F>(py: Python<'py>, items: &[T], n_threads: usize, f: F) -> PyResult<&'py PyList>
+where
+    T: Sync,
+    R: ToPyObject + Send,
+    F: Fn(&T) -> R + Sync,
+{
+    let results: Vec<R> = py.allow_threads(|| par_chunks(items, n_threads, &f));
+
+    let mut list_builder = PyListBuilder::with_capacity(py, results.len())?;
+    for result in results {
+        list_builder.push_alt(py, result)?;
+    }
+    list_builder.get(py)
+}
+
+fn par_chunks<T, R, F>(items: &[T], n_threads: usize, f: &F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+{
+    if n_threads <= 1 || items.len() <= 1 {
+        return items.iter().map(f).collect();
+    }
+    let chunk_size = items.len().div_ceil(n_threads);
+    thread::scope(|scope| {
+        let handles: Vec<_> = items
+            .chunks(chunk_size)
+            .map(|chunk| scope.spawn(move || chunk.iter().map(f).collect::<Vec<R>>()))
+            .collect();
+        let mut results = Vec::with_capacity(items.len());
+        for handle in handles {
+            results.extend(handle.join().expect("worker thread panicked"));
+        }
+        results
+    })
+}
@@ -0,0 +1,121 @@
+use pyo3::prelude::*;
+use pyo3::types::{PyList, PyString};
+
+use crate::PyListBuilder;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Affix {
+    Prefix,
+    Suffix,
+}
+
+pub fn count_prefix(list: &PyList, prefix: &str) -> PyResult<usize> {
+    count_affix(list, prefix, Affix::Prefix)
+}
+
+pub fn count_suffix(list: &PyList, suffix: &str) -> PyResult<usize> {
+    count_affix(list, suffix, Affix::Suffix)
+}
+
+pub fn filter_prefix<'py>(list: &'py PyList, prefix: &str) -> PyResult<&'py PyList> {
+    filter_affix(list, prefix, Affix::Prefix)
+}
+
+pub fn filter_suffix<'py>(list: &'py PyList, suffix: &str) -> PyResult<&'py PyList> {
+    filter_affix(list, suffix, Affix::Suffix)
+}
+
+fn count_affix(list: &PyList, needle: &str, affix: Affix) -> PyResult<usize> {
+    let needle = Needle::new(needle);
+    let mut count = 0;
+    for item in list.iter() {
+        if needle.matches(item.downcast()?, affix)? {
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
+fn filter_affix<'py>(list: &'py PyList, needle: &str, affix: Affix) -> PyResult<&'py PyList> {
+    let needle = Needle::new(needle);
+    let mut matched: Vec<&PyAny> = Vec::new();
+    for item in list.iter() {
+        if needle.matches(item.downcast()?, affix)? {
+            matched.push(item);
+        }
+    }
+    let py = list.py();
+    let mut list_builder = PyListBuilder::with_capacity(py, matched.len())?;
+    for item in matched {
+        list_builder.push(py, item)?;
+    }
+    list_builder.get(py)
+}
+
+// the needle encoded once for each of the string kinds CPython uses, `None` where the needle has
+// characters too wide for that kind, in which case no string of that kind can match
+#[cfg(not(Py_LIMITED_API))]
+struct Needle {
+    ucs1: Option<Vec<u8>>,
+    ucs2: Option<Vec<u16>>,
+    ucs4: Vec<u32>,
+}
+
+#[cfg(Py_LIMITED_API)]
+struct Needle(String);
+
+impl Needle {
+    #[cfg(not(Py_LIMITED_API))]
+    fn new(str: &str) -> Self {
+        let ucs4: Vec<u32> = str.chars().map(|c| c as u32).collect();
+        let ucs1 = ucs4.iter().map(|&c| u8::try_from(c).ok()).collect();
+        let ucs2 = ucs4.iter().map(|&c| u16::try_from(c).ok()).collect();
+        Self { ucs1, ucs2, ucs4 }
+    }
+
+    #[cfg(Py_LIMITED_API)]
+    fn new(str: &str) -> Self {
+        Self(str.to_string())
+    }
+
+    #[cfg(not(Py_LIMITED_API))]
+    fn matches(&self, py_str: &PyString, affix: Affix) -> PyResult<bool> {
+        use pyo3::{ffi, AsPyPointer};
+        use std::slice;
+
+        let ptr = py_str.as_ptr();
+        unsafe {
+            let len = ffi::PyUnicode_GET_LENGTH(ptr) as usize;
+            let data = ffi::PyUnicode_DATA(ptr);
+            let m = match ffi::PyUnicode_KIND(ptr) {
+                ffi::PyUnicode_1BYTE_KIND => match self.ucs1 {
+                    Some(ref n) => affix_eq(slice::from_raw_parts(data as *const u8, len), n, affix),
+                    None => false,
+                },
+                ffi::PyUnicode_2BYTE_KIND => match self.ucs2 {
+                    Some(ref n) => affix_eq(slice::from_raw_parts(data as *const u16, len), n, affix),
+                    None => false,
+                },
+                _ => affix_eq(slice::from_raw_parts(data as *const u32, len), &self.ucs4, affix),
+            };
+            Ok(m)
+        }
+    }
+
+    #[cfg(Py_LIMITED_API)]
+    fn matches(&self, py_str: &PyString, affix: Affix) -> PyResult<bool> {
+        let s = py_str.to_str()?;
+        Ok(match affix {
+            Affix::Prefix => s.starts_with(&self.0),
+            Affix::Suffix => s.ends_with(&self.0),
+        })
+    }
+}
+
+#[cfg(not(Py_LIMITED_API))]
+fn affix_eq<T: PartialEq>(haystack: &[T], needle: &[T], affix: Affix) -> bool {
+    match affix {
+        Affix::Prefix => haystack.starts_with(needle),
+        Affix::Suffix => haystack.ends_with(needle),
+    }
+}
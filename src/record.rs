@@ -0,0 +1,158 @@
+use std::ffi::CString;
+use std::ptr;
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::{PyTuple, PyType};
+use pyo3::{ffi, AsPyPointer};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordKind {
+    NamedTuple,
+    StructSequence,
+}
+
+// a record class and its field names, declared once and then used to build any number of records
+pub struct RecordType {
+    kind: RecordKind,
+    class: Py<PyType>,
+    fields: Vec<String>,
+}
+
+impl RecordType {
+    // the same class `collections.namedtuple(name, fields)` would create
+    pub fn namedtuple(py: Python, name: &str, fields: &[&str]) -> PyResult<Self> {
+        let namedtuple = py.import("collections")?.getattr("namedtuple")?;
+        let class: &PyType = namedtuple.call1((name, fields.to_vec()))?.downcast()?;
+        Ok(Self {
+            kind: RecordKind::NamedTuple,
+            class: class.into(),
+            fields: fields.iter().map(|f| f.to_string()).collect(),
+        })
+    }
+
+    // a new `PyStructSequence` type like `os.stat_result`, all fields are visible in the sequence.
+    // CPython keeps pointers to the name and field names for the life of the type, which can
+    // outlive this `RecordType`, so they're leaked. The field array itself is only read while the
+    // type is created.
+    pub fn struct_sequence(py: Python, name: &str, fields: &[&str]) -> PyResult<Self> {
+        let c_name = leak_c_str(name)?;
+        let mut c_fields = Vec::with_capacity(fields.len() + 1);
+        for field in fields {
+            c_fields.push(ffi::PyStructSequence_Field {
+                name: leak_c_str(field)?,
+                doc: ptr::null_mut(),
+            });
+        }
+        // the field list is terminated by a null name
+        c_fields.push(ffi::PyStructSequence_Field {
+            name: ptr::null_mut(),
+            doc: ptr::null_mut(),
+        });
+        let mut desc = ffi::PyStructSequence_Desc {
+            name: c_name,
+            doc: ptr::null_mut(),
+            fields: c_fields.as_mut_ptr(),
+            n_in_sequence: fields
+                .len()
+                .try_into()
+                .map_err(|_| PyValueError::new_err("too many fields"))?,
+        };
+        let class: Py<PyType> = unsafe {
+            let type_ptr = ffi::PyStructSequence_NewType(&mut desc);
+            Py::from_owned_ptr_or_err(py, type_ptr as *mut ffi::PyObject)?
+        };
+        Ok(Self {
+            kind: RecordKind::StructSequence,
+            class,
+            fields: fields.iter().map(|f| f.to_string()).collect(),
+        })
+    }
+
+    pub fn kind(&self) -> RecordKind {
+        self.kind
+    }
+
+    pub fn class<'py>(&self, py: Python<'py>) -> &'py PyType {
+        self.class.clone_ref(py).into_ref(py)
+    }
+
+    pub fn fields(&self) -> &[String] {
+        &self.fields
+    }
+
+    // records are short, so a linear scan beats hashing the name
+    pub fn field_index(&self, field: &str) -> Option<usize> {
+        self.fields.iter().position(|f| f == field)
+    }
+
+    pub fn builder(&self, py: Python) -> PyResult<RecordBuilder<'_>> {
+        let len = self.fields.len() as ffi::Py_ssize_t;
+        let type_ptr = self.class.as_ptr() as *mut ffi::PyTypeObject;
+        let tuple: Py<PyTuple> = unsafe {
+            // allocate an instance of the class directly, skipping `__new__`, with every item null
+            let ptr = match self.kind {
+                RecordKind::NamedTuple => ffi::PyType_GenericAlloc(type_ptr, len),
+                RecordKind::StructSequence => ffi::PyStructSequence_New(type_ptr),
+            };
+            Py::from_owned_ptr_or_err(py, ptr)?
+        };
+        Ok(RecordBuilder {
+            record_type: self,
+            tuple,
+            set: 0,
+        })
+    }
+}
+
+fn leak_c_str(s: &str) -> PyResult<*mut std::os::raw::c_char> {
+    let c_str = CString::new(s).map_err(|_| PyValueError::new_err(format!("{:?} contains a null byte", s)))?;
+    Ok(c_str.into_raw())
+}
+
+// fields can be pushed in any order, unset fields are null until `get` checks them
+pub struct RecordBuilder<'a> {
+    record_type: &'a RecordType,
+    tuple: Py<PyTuple>,
+    // the number of fields set so far
+    set: usize,
+}
+
+impl RecordBuilder<'_> {
+    pub fn push(&mut self, py: Python, field: &str, item: impl ToPyObject) -> PyResult<()> {
+        match self.record_type.field_index(field) {
+            Some(index) => self.push_index(py, index, item),
+            None => Err(PyValueError::new_err(format!("unknown field '{}'", field))),
+        }
+    }
+
+    pub fn push_index(&mut self, py: Python, index: usize, item: impl ToPyObject) -> PyResult<()> {
+        let field = self
+            .record_type
+            .fields
+            .get(index)
+            .ok_or_else(|| PyValueError::new_err(format!("field index {} out of range", index)))?;
+        let ptr = self.tuple.as_ptr();
+        unsafe {
+            if !ffi::PyTuple_GetItem(ptr, index as ffi::Py_ssize_t).is_null() {
+                return Err(PyValueError::new_err(format!("field '{}' already set", field)));
+            }
+            // the record was just allocated, so this can't fail
+            ffi::PyTuple_SetItem(ptr, index as ffi::Py_ssize_t, item.to_object(py).into_ptr());
+        }
+        self.set += 1;
+        Ok(())
+    }
+
+    pub fn get(self, py: Python<'_>) -> PyResult<&PyTuple> {
+        if self.set < self.record_type.fields.len() {
+            let ptr = self.tuple.as_ptr();
+            for (index, field) in self.record_type.fields.iter().enumerate() {
+                if unsafe { ffi::PyTuple_GetItem(ptr, index as ffi::Py_ssize_t) }.is_null() {
+                    return Err(PyValueError::new_err(format!("missing field '{}'", field)));
+                }
+            }
+        }
+        Ok(self.tuple.into_ref(py))
+    }
+}
@@ -0,0 +1,218 @@
+use pyo3::exceptions::{PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyList;
+use pyo3::{ffi, AsPyPointer};
+
+use crate::typed_list::TypedItem;
+use crate::{PyListBuilder, PyTupleBuilder};
+
+// the type a column's values must have, `None` is accepted in any column like a NULL in a database row
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnType {
+    Any,
+    Int,
+    Float,
+    Bool,
+    Str,
+}
+
+impl ColumnType {
+    fn name(self) -> &'static str {
+        match self {
+            ColumnType::Any => "any",
+            ColumnType::Int => "int",
+            ColumnType::Float => "float",
+            ColumnType::Bool => "bool",
+            ColumnType::Str => "str",
+        }
+    }
+
+    // `ptr` must be a valid object, subclasses are accepted but `bool` isn't an `int` here
+    unsafe fn accepts(self, ptr: *mut ffi::PyObject) -> bool {
+        match self {
+            ColumnType::Any => true,
+            _ if ptr == ffi::Py_None() => true,
+            ColumnType::Int => ffi::PyLong_Check(ptr) != 0 && ffi::PyBool_Check(ptr) == 0,
+            ColumnType::Float => ffi::PyFloat_Check(ptr) != 0,
+            ColumnType::Bool => ffi::PyBool_Check(ptr) != 0,
+            ColumnType::Str => ffi::PyUnicode_Check(ptr) != 0,
+        }
+    }
+}
+
+// a `list[tuple[...]]` where every tuple has the same columns. Rows can be pushed whole with
+// `push_row`, or a column at a time with `push` and the typed pushes, a row is added to the list as
+// soon as its last column is pushed.
+pub struct RowSetBuilder {
+    schema: Vec<ColumnType>,
+    rows: PyListBuilder,
+    // the row being filled by the per-column pushes
+    row: Option<PyTupleBuilder>,
+}
+
+impl RowSetBuilder {
+    // every column accepts any type
+    pub fn with_capacity(py: Python, rows: usize, columns: usize) -> PyResult<Self> {
+        Self::with_schema(py, rows, &vec![ColumnType::Any; columns])
+    }
+
+    pub fn with_schema(py: Python, rows: usize, schema: &[ColumnType]) -> PyResult<Self> {
+        if schema.is_empty() {
+            return Err(PyValueError::new_err("row set must have at least one column"));
+        }
+        Ok(Self {
+            schema: schema.to_vec(),
+            rows: PyListBuilder::with_capacity(py, rows)?,
+            row: None,
+        })
+    }
+
+    // on error nothing is added, any items already taken from `row` are dropped
+    pub fn push_row<T: ToPyObject>(&mut self, py: Python, row: impl IntoIterator<Item = T>) -> PyResult<()> {
+        if self.row.is_some() {
+            return Err(PyValueError::new_err("push_row() called with a partial row in progress"));
+        }
+        self.check_capacity()?;
+        let mut tuple_builder = PyTupleBuilder::with_capacity(py, self.columns())?;
+        for item in row {
+            let column = tuple_builder.len();
+            if column == self.columns() {
+                return Err(PyValueError::new_err(format!(
+                    "row has more than {} items",
+                    self.columns()
+                )));
+            }
+            let obj = item.to_object(py);
+            self.check_column(py, column, &obj)?;
+            unsafe { tuple_builder.push_owned_ptr(obj.into_ptr())? };
+        }
+        if tuple_builder.remaining() > 0 {
+            return Err(PyValueError::new_err(format!(
+                "row has {} items, expected {}",
+                tuple_builder.len(),
+                self.columns()
+            )));
+        }
+        self.finish_row(tuple_builder)
+    }
+
+    pub fn push(&mut self, py: Python, item: impl ToPyObject) -> PyResult<()> {
+        let obj = item.to_object(py);
+        self.check_column(py, self.next_column(), &obj)?;
+        self.push_checked(py, obj)
+    }
+
+    // the column type is checked before the object is created, which skips `to_object`
+    pub fn push_i64(&mut self, py: Python, value: i64) -> PyResult<()> {
+        self.push_typed(py, ColumnType::Int, value)
+    }
+
+    pub fn push_f64(&mut self, py: Python, value: f64) -> PyResult<()> {
+        self.push_typed(py, ColumnType::Float, value)
+    }
+
+    pub fn push_bool(&mut self, py: Python, value: bool) -> PyResult<()> {
+        self.push_typed(py, ColumnType::Bool, value)
+    }
+
+    pub fn push_str(&mut self, py: Python, value: &str) -> PyResult<()> {
+        self.push_typed(py, ColumnType::Str, value)
+    }
+
+    pub fn push_none(&mut self, py: Python) -> PyResult<()> {
+        self.push_checked(py, py.None())
+    }
+
+    // drops the columns pushed so far for the current row
+    pub fn discard_row(&mut self) {
+        self.row = None;
+    }
+
+    pub fn columns(&self) -> usize {
+        self.schema.len()
+    }
+
+    pub fn schema(&self) -> &[ColumnType] {
+        &self.schema
+    }
+
+    // complete rows only
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    pub fn get(self, py: Python<'_>) -> PyResult<&PyList> {
+        match self.row {
+            Some(_) => Err(PyValueError::new_err("row set has a partial row")),
+            None => self.rows.get(py),
+        }
+    }
+
+    // any partial row is dropped, the list is truncated to the complete rows
+    pub fn get_incomplete(self, py: Python<'_>) -> &PyList {
+        self.rows.get_incomplete(py)
+    }
+
+    fn next_column(&self) -> usize {
+        self.row.as_ref().map_or(0, |row| row.len())
+    }
+
+    fn check_column(&self, py: Python, column: usize, obj: &PyObject) -> PyResult<()> {
+        let column_type = self.schema[column];
+        if unsafe { column_type.accepts(obj.as_ptr()) } {
+            Ok(())
+        } else {
+            let type_name = obj.as_ref(py).get_type().name()?;
+            Err(column_error(column, column_type, type_name))
+        }
+    }
+
+    fn push_typed<T: TypedItem>(&mut self, py: Python, value_type: ColumnType, value: T) -> PyResult<()> {
+        let column = self.next_column();
+        let column_type = self.schema[column];
+        if column_type != ColumnType::Any && column_type != value_type {
+            return Err(column_error(column, column_type, value_type.name()));
+        }
+        let obj: PyObject = unsafe { Py::from_owned_ptr_or_err(py, value.into_object_ptr(py))? };
+        self.push_checked(py, obj)
+    }
+
+    // `obj` must already have been checked against the next column's type
+    fn push_checked(&mut self, py: Python, obj: PyObject) -> PyResult<()> {
+        let mut tuple_builder = match self.row.take() {
+            Some(tuple_builder) => tuple_builder,
+            None => {
+                self.check_capacity()?;
+                PyTupleBuilder::with_capacity(py, self.columns())?
+            }
+        };
+        // can't fail, the row is added as soon as it's full
+        unsafe { tuple_builder.push_owned_ptr(obj.into_ptr())? };
+        if tuple_builder.remaining() == 0 {
+            self.finish_row(tuple_builder)?;
+        } else {
+            self.row = Some(tuple_builder);
+        }
+        Ok(())
+    }
+
+    fn check_capacity(&self) -> PyResult<()> {
+        match self.rows.remaining() > 0 {
+            true => Ok(()),
+            false => Err(PyValueError::new_err("push() exceeded row capacity")),
+        }
+    }
+
+    // `tuple_builder` must be full and `check_capacity` must have passed before it was started
+    fn finish_row(&mut self, tuple_builder: PyTupleBuilder) -> PyResult<()> {
+        unsafe { self.rows.push_owned_ptr(tuple_builder.into_ptr()) }
+    }
+}
+
+fn column_error(column: usize, column_type: ColumnType, got: &str) -> PyErr {
+    PyTypeError::new_err(format!("column {} expects {}, got {}", column, column_type.name(), got))
+}
@@ -0,0 +1,177 @@
+use std::os::raw::c_char;
+use std::ptr;
+use std::slice;
+use std::str;
+
+use pyo3::exceptions::{PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyList, PyString};
+use pyo3::{ffi, AsPyPointer};
+
+use crate::extract::{try_extract_str, ExtractError};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StrPath {
+    // read straight from the compact ASCII buffer
+    CompactAscii,
+    // went through `PyUnicode_AsUTF8AndSize`, which may encode and cache the UTF-8 data
+    Utf8,
+}
+
+pub fn borrow_str(py_str: &PyString) -> PyResult<(&str, StrPath)> {
+    let ptr = py_str.as_ptr();
+
+    if let Some(s) = unsafe { compact_ascii_str(ptr) } {
+        return Ok((s, StrPath::CompactAscii));
+    }
+    let s = unsafe { utf8_and_size(py_str.py(), ptr)? };
+    Ok((s, StrPath::Utf8))
+}
+
+// `ptr` must be a `str` instance
+#[cfg(not(Py_LIMITED_API))]
+pub(crate) unsafe fn compact_ascii_str<'a>(ptr: *mut ffi::PyObject) -> Option<&'a str> {
+    if ffi::PyUnicode_IS_COMPACT_ASCII(ptr) != 0 {
+        let data = ffi::PyUnicode_DATA(ptr) as *const u8;
+        let len = ffi::PyUnicode_GET_LENGTH(ptr) as usize;
+        // ASCII is always valid UTF-8
+        Some(str::from_utf8_unchecked(slice::from_raw_parts(data, len)))
+    } else {
+        None
+    }
+}
+
+#[cfg(Py_LIMITED_API)]
+pub(crate) unsafe fn compact_ascii_str<'a>(_ptr: *mut ffi::PyObject) -> Option<&'a str> {
+    None
+}
+
+unsafe fn utf8_and_size<'a>(py: Python, ptr: *mut ffi::PyObject) -> PyResult<&'a str> {
+    let mut size: ffi::Py_ssize_t = 0;
+    let data: *const c_char = ffi::PyUnicode_AsUTF8AndSize(ptr, &mut size);
+    if data.is_null() {
+        Err(PyErr::take(py).unwrap_or_else(|| PyValueError::new_err("failed to encode string as UTF-8")))
+    } else {
+        let bytes = slice::from_raw_parts(data as *const u8, size as usize);
+        Ok(str::from_utf8_unchecked(bytes))
+    }
+}
+
+// the strs borrow from the list's items, which pyo3's owned object pool keeps alive even if the
+// list is later mutated
+pub fn extract_str_list(list: &PyList) -> PyResult<Vec<&str>> {
+    let mut strs = Vec::with_capacity(list.len());
+    for (index, item) in list.iter().enumerate() {
+        strs.push(list_item_str(index, item)?);
+    }
+    Ok(strs)
+}
+
+pub fn extract_str_arena(list: &PyList) -> PyResult<StrArena> {
+    let mut arena = StrArena::with_capacity(list.len());
+    for (index, item) in list.iter().enumerate() {
+        arena.push(list_item_str(index, item)?);
+    }
+    Ok(arena)
+}
+
+fn list_item_str(index: usize, item: &PyAny) -> PyResult<&str> {
+    try_extract_str(item).map_err(|e| match e {
+        ExtractError::InvalidUtf8 => PyValueError::new_err(format!("item {}: str is not valid UTF-8", index)),
+        _ => match item.get_type().name() {
+            Ok(type_name) => PyTypeError::new_err(format!("item {}: expected str, got {}", index, type_name)),
+            Err(err) => err,
+        },
+    })
+}
+
+// owned strings packed into a single buffer, one allocation for the data regardless of count
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StrArena {
+    data: String,
+    ends: Vec<usize>,
+}
+
+impl StrArena {
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            data: String::new(),
+            ends: Vec::with_capacity(capacity),
+        }
+    }
+
+    pub fn push(&mut self, s: &str) {
+        self.data.push_str(s);
+        self.ends.push(self.data.len());
+    }
+
+    pub fn len(&self) -> usize {
+        self.ends.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ends.is_empty()
+    }
+
+    pub fn get(&self, index: usize) -> Option<&str> {
+        let end = *self.ends.get(index)?;
+        let start = match index {
+            0 => 0,
+            _ => self.ends[index - 1],
+        };
+        Some(&self.data[start..end])
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        (0..self.len()).map(|index| self.get(index).unwrap())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Validation {
+    // raise a ValueError at the first byte that isn't ASCII
+    Strict,
+    // replace any byte that isn't ASCII with `?`
+    Replace,
+}
+
+// skips the UTF-8 decoding `PyString::new` does, the bytes are copied straight into a new compact
+// ASCII string
+pub fn new_ascii_string<'py>(py: Python<'py>, data: &[u8], validation: Validation) -> PyResult<&'py PyString> {
+    match data.iter().position(|b| !b.is_ascii()) {
+        None => unsafe { new_ucs1_string(py, data, 127) },
+        Some(index) => match validation {
+            Validation::Strict => Err(PyValueError::new_err(format!(
+                "byte 0x{:02x} at index {} is not ASCII",
+                data[index], index
+            ))),
+            Validation::Replace => {
+                let replaced: Vec<u8> = data.iter().map(|&b| if b.is_ascii() { b } else { b'?' }).collect();
+                unsafe { new_ucs1_string(py, &replaced, 127) }
+            }
+        },
+    }
+}
+
+// every byte is a valid latin-1 code point so there's nothing to validate, but the data is still
+// scanned so that ASCII-only input gets the compact ASCII layout CPython expects
+pub fn new_latin1_string<'py>(py: Python<'py>, data: &[u8]) -> PyResult<&'py PyString> {
+    let max_char = if data.is_ascii() { 127 } else { 255 };
+    unsafe { new_ucs1_string(py, data, max_char) }
+}
+
+// `max_char` must be the true maximum of `data`, either 127 or 255
+#[cfg(not(Py_LIMITED_API))]
+unsafe fn new_ucs1_string<'py>(py: Python<'py>, data: &[u8], max_char: ffi::Py_UCS4) -> PyResult<&'py PyString> {
+    let ptr = ffi::PyUnicode_New(data.len() as ffi::Py_ssize_t, max_char);
+    let py_str: &PyAny = py.from_owned_ptr_or_err(ptr)?;
+    ptr::copy_nonoverlapping(data.as_ptr(), ffi::PyUnicode_DATA(ptr) as *mut u8, data.len());
+    Ok(py_str.downcast_unchecked())
+}
+
+#[cfg(Py_LIMITED_API)]
+unsafe fn new_ucs1_string<'py>(py: Python<'py>, data: &[u8], _max_char: ffi::Py_UCS4) -> PyResult<&'py PyString> {
+    let ptr = ffi::PyUnicode_DecodeLatin1(data.as_ptr() as *const c_char, data.len() as ffi::Py_ssize_t, ptr::null());
+    let py_str: &PyAny = py.from_owned_ptr_or_err(ptr)?;
+    Ok(py_str.downcast_unchecked())
+}
@@ -0,0 +1,86 @@
+use ahash::RandomState;
+use pyo3::prelude::*;
+use pyo3::types::PyString;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+}
+
+impl CacheStats {
+    pub fn hit_rate(&self) -> f64 {
+        match self.hits + self.misses {
+            0 => 0.0,
+            total => self.hits as f64 / total as f64,
+        }
+    }
+}
+
+struct Slot {
+    hash: u64,
+    key: Box<str>,
+    value: Py<PyString>,
+}
+
+// a two-way cache: each string can only live in one of the pair of slots its hash points at, and a
+// miss replaces whatever was in its own slot if neither is free. No LRU bookkeeping, so hits and
+// misses are both O(1), at the cost of some avoidable evictions when three hot strings share a pair.
+pub struct StringCache {
+    slots: Vec<Option<Slot>>,
+    hash_builder: RandomState,
+    stats: CacheStats,
+}
+
+impl StringCache {
+    // `capacity` is rounded up to a whole number of pairs
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            slots: (0..capacity.max(2).next_multiple_of(2)).map(|_| None).collect(),
+            hash_builder: RandomState::new(),
+            stats: CacheStats::default(),
+        }
+    }
+
+    pub fn get(&mut self, py: Python, s: &str) -> Py<PyString> {
+        let hash = self.hash_builder.hash_one(s);
+        let index = (hash % self.slots.len() as u64) as usize;
+        let pair = index & !1;
+        for slot in self.slots[pair..pair + 2].iter().flatten() {
+            if slot.hash == hash && slot.key.as_ref() == s {
+                self.stats.hits += 1;
+                return slot.value.clone_ref(py);
+            }
+        }
+        self.stats.misses += 1;
+        let target = match (&self.slots[index], &self.slots[index ^ 1]) {
+            (Some(_), None) => index ^ 1,
+            (None, _) => index,
+            (Some(_), Some(_)) => {
+                self.stats.evictions += 1;
+                index
+            }
+        };
+        let value: Py<PyString> = PyString::new(py, s).into();
+        self.slots[target] = Some(Slot {
+            hash,
+            key: s.into(),
+            value: value.clone_ref(py),
+        });
+        value
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        self.stats
+    }
+
+    pub fn clear(&mut self) {
+        self.slots.iter_mut().for_each(|slot| *slot = None);
+        self.stats = CacheStats::default();
+    }
+}
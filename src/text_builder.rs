@@ -0,0 +1,233 @@
+use std::ptr;
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyString};
+use pyo3::{ffi, AsPyPointer};
+
+fn ssize(len: usize, what: &str) -> PyResult<ffi::Py_ssize_t> {
+    len.try_into()
+        .map_err(|_| PyValueError::new_err(format!("{} len out of range", what)))
+}
+
+// the capacity to grow to so that `needed` bytes fit, at least doubling to keep pushes amortised O(1)
+fn grown_capacity(capacity: ffi::Py_ssize_t, needed: ffi::Py_ssize_t) -> ffi::Py_ssize_t {
+    needed.max(capacity.saturating_mul(2)).max(8)
+}
+
+// bytes written straight into a `bytes` object which is grown in place as needed, then shrunk to
+// the written length by `get`
+pub struct PyBytesBuilder {
+    bytes: Py<PyBytes>,
+    len: ffi::Py_ssize_t,
+    capacity: ffi::Py_ssize_t,
+}
+
+impl PyBytesBuilder {
+    pub fn with_capacity(py: Python, capacity: usize) -> PyResult<Self> {
+        let capacity = ssize(capacity, "bytes")?;
+        let bytes = unsafe { new_bytes(py, capacity)? };
+        Ok(Self { bytes, len: 0, capacity })
+    }
+
+    pub fn push(&mut self, py: Python, data: &[u8]) -> PyResult<()> {
+        let needed = ssize(data.len(), "bytes")?
+            .checked_add(self.len)
+            .ok_or_else(|| PyValueError::new_err("bytes len out of range"))?;
+        unsafe {
+            if needed > self.capacity {
+                let capacity = grown_capacity(self.capacity, needed);
+                resize_bytes(py, &mut self.bytes, self.len, capacity)?;
+                self.capacity = capacity;
+            }
+            let dest = ffi::PyBytes_AsString(self.bytes.as_ptr()) as *mut u8;
+            ptr::copy_nonoverlapping(data.as_ptr(), dest.offset(self.len), data.len());
+        }
+        self.len = needed;
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.len as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get(mut self, py: Python<'_>) -> PyResult<&PyBytes> {
+        if self.len < self.capacity {
+            unsafe { resize_bytes(py, &mut self.bytes, self.len, self.len)? };
+        }
+        Ok(self.bytes.into_ref(py))
+    }
+}
+
+unsafe fn new_bytes(py: Python, len: ffi::Py_ssize_t) -> PyResult<Py<PyBytes>> {
+    // a null pointer leaves the contents uninitialised
+    let ptr = ffi::PyBytes_FromStringAndSize(ptr::null(), len);
+    Py::from_owned_ptr_or_err(py, ptr)
+}
+
+// `bytes` must be the only reference to the object, the first `len` bytes are kept
+#[cfg(not(Py_LIMITED_API))]
+unsafe fn resize_bytes(
+    py: Python,
+    bytes: &mut Py<PyBytes>,
+    _len: ffi::Py_ssize_t,
+    new_len: ffi::Py_ssize_t,
+) -> PyResult<()> {
+    let mut ptr = bytes.as_ptr();
+    if ffi::_PyBytes_Resize(&mut ptr, new_len) == 0 {
+        // our reference moved into `ptr` (possibly reallocated), so overwrite without a decref
+        ptr::write(bytes, Py::from_owned_ptr(py, ptr));
+        Ok(())
+    } else {
+        // the old object has already been freed, leave an empty one in its place so drop is sound
+        ptr::write(bytes, PyBytes::new(py, b"").into());
+        Err(PyErr::fetch(py))
+    }
+}
+
+#[cfg(Py_LIMITED_API)]
+unsafe fn resize_bytes(
+    py: Python,
+    bytes: &mut Py<PyBytes>,
+    len: ffi::Py_ssize_t,
+    new_len: ffi::Py_ssize_t,
+) -> PyResult<()> {
+    let new = new_bytes(py, new_len)?;
+    let src = ffi::PyBytes_AsString(bytes.as_ptr()) as *const u8;
+    let dest = ffi::PyBytes_AsString(new.as_ptr()) as *mut u8;
+    ptr::copy_nonoverlapping(src, dest, len.min(new_len) as usize);
+    *bytes = new;
+    Ok(())
+}
+
+enum StrBuffer {
+    // every push so far was ASCII, written straight into a compact ASCII str object
+    #[cfg(not(Py_LIMITED_API))]
+    Ascii {
+        string: Py<PyString>,
+        len: ffi::Py_ssize_t,
+        capacity: ffi::Py_ssize_t,
+    },
+    // once the result can't be compact ASCII its kind isn't known until the end, so build in Rust
+    Utf8(String),
+}
+
+pub struct PyStringBuilder {
+    buffer: StrBuffer,
+}
+
+impl PyStringBuilder {
+    // `capacity` is in bytes of UTF-8, which for ASCII data is also the number of characters
+    #[cfg(not(Py_LIMITED_API))]
+    pub fn with_capacity(py: Python, capacity: usize) -> PyResult<Self> {
+        let capacity = ssize(capacity, "str")?;
+        let string = unsafe { new_ascii(py, capacity)? };
+        Ok(Self {
+            buffer: StrBuffer::Ascii { string, len: 0, capacity },
+        })
+    }
+
+    #[cfg(Py_LIMITED_API)]
+    pub fn with_capacity(_py: Python, capacity: usize) -> PyResult<Self> {
+        ssize(capacity, "str")?;
+        Ok(Self {
+            buffer: StrBuffer::Utf8(String::with_capacity(capacity)),
+        })
+    }
+
+    pub fn push(&mut self, py: Python, s: &str) -> PyResult<()> {
+        match &mut self.buffer {
+            #[cfg(not(Py_LIMITED_API))]
+            StrBuffer::Ascii { string, len, capacity } => {
+                if !s.is_ascii() {
+                    let mut utf8 = String::with_capacity((*capacity as usize).max(*len as usize + s.len()));
+                    utf8.push_str(unsafe { ascii_data(string, *len) });
+                    utf8.push_str(s);
+                    self.buffer = StrBuffer::Utf8(utf8);
+                    return Ok(());
+                }
+                let needed = ssize(s.len(), "str")?
+                    .checked_add(*len)
+                    .ok_or_else(|| PyValueError::new_err("str len out of range"))?;
+                unsafe {
+                    if needed > *capacity {
+                        let new_capacity = grown_capacity(*capacity, needed);
+                        resize_ascii(py, string, new_capacity)?;
+                        *capacity = new_capacity;
+                    }
+                    let dest = ffi::PyUnicode_DATA(string.as_ptr()) as *mut u8;
+                    ptr::copy_nonoverlapping(s.as_ptr(), dest.offset(*len), s.len());
+                }
+                *len = needed;
+            }
+            StrBuffer::Utf8(utf8) => {
+                let _ = py;
+                utf8.push_str(s);
+            }
+        }
+        Ok(())
+    }
+
+    // in bytes of UTF-8
+    pub fn len(&self) -> usize {
+        match &self.buffer {
+            #[cfg(not(Py_LIMITED_API))]
+            StrBuffer::Ascii { len, .. } => *len as usize,
+            StrBuffer::Utf8(utf8) => utf8.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn get(self, py: Python<'_>) -> PyResult<&PyString> {
+        match self.buffer {
+            #[cfg(not(Py_LIMITED_API))]
+            StrBuffer::Ascii {
+                mut string,
+                len,
+                capacity,
+            } => {
+                if len < capacity {
+                    unsafe { resize_ascii(py, &mut string, len)? };
+                }
+                Ok(string.into_ref(py))
+            }
+            StrBuffer::Utf8(utf8) => Ok(PyString::new(py, &utf8)),
+        }
+    }
+}
+
+#[cfg(not(Py_LIMITED_API))]
+unsafe fn new_ascii(py: Python, len: ffi::Py_ssize_t) -> PyResult<Py<PyString>> {
+    // the contents are uninitialised until written, but nothing reads past the written length
+    let ptr = ffi::PyUnicode_New(len, 127);
+    Py::from_owned_ptr_or_err(py, ptr)
+}
+
+#[cfg(not(Py_LIMITED_API))]
+unsafe fn ascii_data(string: &Py<PyString>, len: ffi::Py_ssize_t) -> &str {
+    let data = ffi::PyUnicode_DATA(string.as_ptr()) as *const u8;
+    // only ASCII has been written
+    std::str::from_utf8_unchecked(std::slice::from_raw_parts(data, len as usize))
+}
+
+// `string` must be the only reference to the object and must not have been hashed yet, otherwise
+// CPython resizes a copy rather than reallocating in place
+#[cfg(not(Py_LIMITED_API))]
+unsafe fn resize_ascii(py: Python, string: &mut Py<PyString>, new_len: ffi::Py_ssize_t) -> PyResult<()> {
+    let mut ptr = string.as_ptr();
+    if ffi::PyUnicode_Resize(&mut ptr, new_len) == 0 {
+        // our reference moved into `ptr` (possibly reallocated), so overwrite without a decref
+        ptr::write(string, Py::from_owned_ptr(py, ptr));
+        Ok(())
+    } else {
+        // unlike `_PyBytes_Resize` the original object is left untouched on failure
+        Err(PyErr::fetch(py))
+    }
+}
@@ -0,0 +1,118 @@
+use std::marker::PhantomData;
+
+use pyo3::exceptions::PyValueError;
+use pyo3::ffi;
+use pyo3::prelude::*;
+use pyo3::types::{PyAny, PyList};
+
+use crate::PyListBuilder;
+
+// an item `TypedListBuilder` can push. Any `ToPyObject` type can opt in with an empty
+// `impl TypedItem for X {}`, which goes through `to_object`; the types with a fast path build the
+// object directly instead.
+pub trait TypedItem: ToPyObject + Sized {
+    // must return a new reference, or null with a Python error set
+    fn into_object_ptr(self, py: Python) -> *mut ffi::PyObject {
+        self.to_object(py).into_ptr()
+    }
+}
+
+impl TypedItem for i64 {
+    fn into_object_ptr(self, _py: Python) -> *mut ffi::PyObject {
+        unsafe { ffi::PyLong_FromLongLong(self) }
+    }
+}
+
+impl TypedItem for f64 {
+    fn into_object_ptr(self, _py: Python) -> *mut ffi::PyObject {
+        unsafe { ffi::PyFloat_FromDouble(self) }
+    }
+}
+
+impl TypedItem for bool {
+    // no allocation at all, just another reference to one of the singletons
+    fn into_object_ptr(self, _py: Python) -> *mut ffi::PyObject {
+        unsafe {
+            let ptr = match self {
+                true => ffi::Py_True(),
+                false => ffi::Py_False(),
+            };
+            ffi::Py_INCREF(ptr);
+            ptr
+        }
+    }
+}
+
+impl TypedItem for &str {
+    // skips registering the new string in pyo3's owned object pool like `PyString::new` does
+    fn into_object_ptr(self, _py: Python) -> *mut ffi::PyObject {
+        unsafe { ffi::PyUnicode_FromStringAndSize(self.as_ptr() as *const _, self.len() as ffi::Py_ssize_t) }
+    }
+}
+
+// already an object, so it's handed over without touching the refcount
+impl<T> TypedItem for Py<T> {
+    fn into_object_ptr(self, _py: Python) -> *mut ffi::PyObject {
+        self.into_ptr()
+    }
+}
+
+impl TypedItem for i8 {}
+impl TypedItem for i16 {}
+impl TypedItem for i32 {}
+impl TypedItem for u8 {}
+impl TypedItem for u16 {}
+impl TypedItem for u32 {}
+impl TypedItem for u64 {}
+impl TypedItem for usize {}
+impl TypedItem for f32 {}
+impl TypedItem for String {}
+impl TypedItem for &PyAny {}
+
+// like `PyListBuilder` but every item must be a `T`, so a list can't end up with mixed types by
+// accident
+pub struct TypedListBuilder<T: TypedItem> {
+    builder: PyListBuilder,
+    item_type: PhantomData<T>,
+}
+
+impl<T: TypedItem> TypedListBuilder<T> {
+    pub fn with_capacity(py: Python, capacity: usize) -> PyResult<Self> {
+        Ok(Self {
+            builder: PyListBuilder::with_capacity(py, capacity)?,
+            item_type: PhantomData,
+        })
+    }
+
+    pub fn push(&mut self, py: Python, item: T) -> PyResult<()> {
+        // checked up front so a full list doesn't cost an allocation
+        if self.builder.remaining() == 0 {
+            return Err(PyValueError::new_err("push() exceeded list capacity"));
+        }
+        let obj_ptr = item.into_object_ptr(py);
+        if obj_ptr.is_null() {
+            return Err(PyErr::fetch(py));
+        }
+        unsafe { self.builder.push_owned_ptr(obj_ptr) }
+    }
+
+    pub fn len(&self) -> usize {
+        self.builder.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.builder.is_empty()
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.builder.remaining()
+    }
+
+    pub fn get(self, py: Python<'_>) -> PyResult<&PyList> {
+        self.builder.get(py)
+    }
+
+    pub fn get_incomplete(self, py: Python<'_>) -> &PyList {
+        self.builder.get_incomplete(py)
+    }
+}
@@ -0,0 +1,151 @@
+use ahash::AHashSet;
+use pyo3::exceptions::{PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyInt, PyList, PyString, PyTuple};
+use pyo3::AsPyPointer;
+
+use crate::extract::{try_extract_bool, try_extract_f64, try_extract_str, ExtractError};
+use crate::int::{extract_int, BigInt, IntValue};
+use crate::kind::{classify, PyKind};
+use crate::{PyDictBuilder, PyListBuilder, PyTupleBuilder};
+
+pub const DEFAULT_MAX_DEPTH: usize = 200;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Int(i64),
+    BigInt(BigInt),
+    Float(f64),
+    Str(String),
+    Array(Vec<Value>),
+    // a vec rather than a map so key order survives the round trip back to Python
+    Object(Vec<(String, Value)>),
+}
+
+pub fn to_value(obj: &PyAny) -> PyResult<Value> {
+    to_value_max_depth(obj, DEFAULT_MAX_DEPTH)
+}
+
+pub fn to_value_max_depth(obj: &PyAny, max_depth: usize) -> PyResult<Value> {
+    let mut converter = ToValue {
+        max_depth,
+        path: AHashSet::new(),
+    };
+    converter.convert(obj)
+}
+
+struct ToValue {
+    max_depth: usize,
+    // containers on the path from the root to the current item, used to spot cycles
+    path: AHashSet<usize>,
+}
+
+impl ToValue {
+    fn convert(&mut self, obj: &PyAny) -> PyResult<Value> {
+        match classify(obj) {
+            PyKind::None => Ok(Value::Null),
+            PyKind::Bool => Ok(Value::Bool(try_extract_bool(obj).unwrap())),
+            PyKind::Int => match extract_int(unsafe { obj.downcast_unchecked::<PyInt>() })? {
+                IntValue::Int(i) => Ok(Value::Int(i)),
+                IntValue::Big(big) => Ok(Value::BigInt(big)),
+            },
+            PyKind::Float => Ok(Value::Float(try_extract_f64(obj).unwrap())),
+            PyKind::Str => Ok(Value::Str(extract_str(obj)?.to_string())),
+            PyKind::List => {
+                let list: &PyList = unsafe { obj.downcast_unchecked() };
+                self.container(obj, |c| list.iter().map(|item| c.convert(item)).collect())
+                    .map(Value::Array)
+            }
+            PyKind::Dict => {
+                let dict: &PyDict = unsafe { obj.downcast_unchecked() };
+                self.container(obj, |c| {
+                    let mut items = Vec::with_capacity(dict.len());
+                    for (key, value) in dict.iter() {
+                        items.push((extract_str(key)?.to_string(), c.convert(value)?));
+                    }
+                    Ok(items)
+                })
+                .map(Value::Object)
+            }
+            PyKind::Other => match obj.downcast::<PyTuple>() {
+                Ok(tuple) => self
+                    .container(obj, |c| tuple.iter().map(|item| c.convert(item)).collect())
+                    .map(Value::Array),
+                Err(_) => Err(PyTypeError::new_err(format!(
+                    "unsupported type: {}",
+                    obj.get_type().name()?
+                ))),
+            },
+        }
+    }
+
+    fn container<T>(&mut self, obj: &PyAny, f: impl FnOnce(&mut Self) -> PyResult<T>) -> PyResult<T> {
+        if self.path.len() >= self.max_depth {
+            return Err(PyValueError::new_err(format!("max depth of {} exceeded", self.max_depth)));
+        }
+        let id = obj.as_ptr() as usize;
+        if !self.path.insert(id) {
+            return Err(PyValueError::new_err("circular reference detected"));
+        }
+        let result = f(self);
+        self.path.remove(&id);
+        result
+    }
+}
+
+fn extract_str(obj: &PyAny) -> PyResult<&str> {
+    try_extract_str(obj).map_err(|e| match e {
+        ExtractError::InvalidUtf8 => PyValueError::new_err("str is not valid UTF-8"),
+        _ => match obj.get_type().name() {
+            Ok(type_name) => PyTypeError::new_err(format!("expected str, got {}", type_name)),
+            Err(err) => err,
+        },
+    })
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct FromValueOptions {
+    // build tuples instead of lists for `Value::Array`
+    pub sequences_as_tuples: bool,
+    // intern dict keys, worthwhile when the same keys are repeated across many objects
+    pub intern_keys: bool,
+}
+
+pub fn from_value(py: Python, value: &Value, options: &FromValueOptions) -> PyResult<PyObject> {
+    match value {
+        Value::Null => Ok(py.None()),
+        Value::Bool(b) => Ok(b.to_object(py)),
+        Value::Int(i) => Ok(i.to_object(py)),
+        Value::BigInt(big) => Ok(big.to_object(py)),
+        Value::Float(f) => Ok(f.to_object(py)),
+        Value::Str(s) => Ok(s.to_object(py)),
+        Value::Array(items) if options.sequences_as_tuples => {
+            let mut tuple_builder = PyTupleBuilder::with_capacity(py, items.len())?;
+            for item in items {
+                tuple_builder.push(py, from_value(py, item, options)?)?;
+            }
+            Ok(tuple_builder.get(py)?.into())
+        }
+        Value::Array(items) => {
+            let mut list_builder = PyListBuilder::with_capacity(py, items.len())?;
+            for item in items {
+                list_builder.push_alt(py, from_value(py, item, options)?)?;
+            }
+            Ok(list_builder.get(py)?.into())
+        }
+        Value::Object(fields) => {
+            let mut dict_builder = PyDictBuilder::with_capacity(py, fields.len())?;
+            for (key, value) in fields {
+                let value = from_value(py, value, options)?;
+                if options.intern_keys {
+                    dict_builder.set_item(py, PyString::intern(py, key), value)?;
+                } else {
+                    dict_builder.set_item(py, key, value)?;
+                }
+            }
+            Ok(dict_builder.get(py).into())
+        }
+    }
+}